@@ -3,9 +3,15 @@
 import { useState, useCallback } from 'react';
 import Link from 'next/link';
 import { Connection, clusterApiUrl, Keypair, Transaction, Signer, SystemProgram, PublicKey } from '@solana/web3.js';
-import { TokenService } from '../services/tokenService';
+import { TokenService, PROGRAM_ID } from '../services/tokenService';
 import { AmmService } from '../services/ammService';
-import { createInitializeMintInstruction, getMinimumBalanceForRentExemptMint, MINT_SIZE, TOKEN_PROGRAM_ID } from '@solana/spl-token';
+import {
+  createInitializeMintInstruction,
+  createInitializeTransferHookInstruction,
+  ExtensionType,
+  getMintLen,
+  TOKEN_2022_PROGRAM_ID,
+} from '@solana/spl-token';
 import { useWallet, useConnection } from '@solana/wallet-adapter-react';
 import { WalletMultiButton } from '@solana/wallet-adapter-react-ui';
 
@@ -223,23 +229,32 @@ export default function Dashboard() {
         return;
       }
 
-      // Create the token mint
+      // Create a Token-2022 mint whose TransferHook extension points at the
+      // transfer hook program, so Token-2022 calls it on every transfer
       const mintKeypair = Keypair.generate();
-      const lamports = await getMinimumBalanceForRentExemptMint(connection);
+      const mintLen = getMintLen([ExtensionType.TransferHook]);
+      const lamports = await connection.getMinimumBalanceForRentExemption(mintLen);
 
       const mintTx = new Transaction().add(
         SystemProgram.createAccount({
           fromPubkey: publicKey,
           newAccountPubkey: mintKeypair.publicKey,
-          space: MINT_SIZE,
+          space: mintLen,
           lamports,
-          programId: TOKEN_PROGRAM_ID
+          programId: TOKEN_2022_PROGRAM_ID
         }),
+        createInitializeTransferHookInstruction(
+          mintKeypair.publicKey,
+          publicKey,
+          PROGRAM_ID,
+          TOKEN_2022_PROGRAM_ID
+        ),
         createInitializeMintInstruction(
           mintKeypair.publicKey,
           parseInt(tokenMetadata.decimals),
           publicKey,
-          publicKey
+          publicKey,
+          TOKEN_2022_PROGRAM_ID
         )
       );
 
@@ -35,7 +35,7 @@ export interface TokenDeploymentResult {
   tokenInfoPDA: PublicKey;
 }
 
-const PROGRAM_ID = new PublicKey('F4RupoT7DMW6dDbkzoyG3R3LndyW9EJEeBp4FvMu9v56');
+export const PROGRAM_ID = new PublicKey('F4RupoT7DMW6dDbkzoyG3R3LndyW9EJEeBp4FvMu9v56');
 
 export class TokenService {
   constructor(private connection: Connection) {}
@@ -84,6 +84,11 @@ export class TokenService {
         PROGRAM_ID
       );
 
+      const [ruleSetPDA] = PublicKey.findProgramAddressSync(
+        [Buffer.from('rules'), mint.toBuffer()],
+        PROGRAM_ID
+      );
+
       // Create initialize instruction
       const initializeIx = new TransactionInstruction({
         programId: PROGRAM_ID,
@@ -93,6 +98,12 @@ export class TokenService {
             isSigner: true,
             isWritable: true,
           },
+          {
+            // Mint authority, recorded on-chain as the token creator
+            pubkey: wallet,
+            isSigner: true,
+            isWritable: false,
+          },
           {
             pubkey: extraAccountMetaListPDA,
             isSigner: false,
@@ -103,6 +114,11 @@ export class TokenService {
             isSigner: false,
             isWritable: true,
           },
+          {
+            pubkey: ruleSetPDA,
+            isSigner: false,
+            isWritable: true,
+          },
           {
             pubkey: mint,
             isSigner: false,
@@ -142,6 +158,7 @@ export class TokenService {
             const addToWhitelistIx = await this.createAddToWhitelistInstruction(
               publicKey,
               tokenInfoPDA,
+              ruleSetPDA,
               wallet
             );
             transaction.add(addToWhitelistIx);
@@ -186,9 +203,15 @@ export class TokenService {
         PROGRAM_ID
       );
 
+      const [ruleSetPDA] = PublicKey.findProgramAddressSync(
+        [Buffer.from('rules'), mint.toBuffer()],
+        PROGRAM_ID
+      );
+
       const instruction = await this.createAddToWhitelistInstruction(
         publicKey,
         tokenInfoPDA,
+        ruleSetPDA,
         authority
       );
 
@@ -226,18 +249,7 @@ export class TokenService {
 
       const instruction = new TransactionInstruction({
         programId: PROGRAM_ID,
-        keys: [
-          {
-            pubkey: tokenInfoPDA,
-            isSigner: false,
-            isWritable: true,
-          },
-          {
-            pubkey: authority,
-            isSigner: true,
-            isWritable: false,
-          },
-        ],
+        keys: this.ruleSettingsKeys(mint, tokenInfoPDA, authority),
         data: Buffer.from([
           // Update whale alert instruction discriminator
           136, 211, 248, 40, 251, 255, 155, 231,
@@ -281,18 +293,7 @@ export class TokenService {
 
       const instruction = new TransactionInstruction({
         programId: PROGRAM_ID,
-        keys: [
-          {
-            pubkey: tokenInfoPDA,
-            isSigner: false,
-            isWritable: true,
-          },
-          {
-            pubkey: authority,
-            isSigner: true,
-            isWritable: false,
-          },
-        ],
+        keys: this.ruleSettingsKeys(mint, tokenInfoPDA, authority),
         data: Buffer.from([
           // Set max transfer limit instruction discriminator
           241, 64, 166, 45, 114, 132, 14, 74,
@@ -440,9 +441,51 @@ export class TokenService {
     return Array.from(new Uint8Array(bn.toArray('le', 8)));
   }
 
+  // Accounts of the instructions that change a rule: the rule set and the
+  // extra account meta list are resized, paid for by the authority
+  private ruleSettingsKeys(mint: PublicKey, tokenInfoPDA: PublicKey, authority: PublicKey) {
+    const [ruleSetPDA] = PublicKey.findProgramAddressSync(
+      [Buffer.from('rules'), mint.toBuffer()],
+      PROGRAM_ID
+    );
+    const [extraAccountMetaListPDA] = PublicKey.findProgramAddressSync(
+      [Buffer.from('extra-account-metas'), mint.toBuffer()],
+      PROGRAM_ID
+    );
+
+    return [
+      {
+        pubkey: tokenInfoPDA,
+        isSigner: false,
+        isWritable: true,
+      },
+      {
+        pubkey: ruleSetPDA,
+        isSigner: false,
+        isWritable: true,
+      },
+      {
+        pubkey: extraAccountMetaListPDA,
+        isSigner: false,
+        isWritable: true,
+      },
+      {
+        pubkey: authority,
+        isSigner: true,
+        isWritable: true,
+      },
+      {
+        pubkey: SystemProgram.programId,
+        isSigner: false,
+        isWritable: false,
+      },
+    ];
+  }
+
   private async createAddToWhitelistInstruction(
     address: PublicKey,
     tokenInfoPDA: PublicKey,
+    ruleSetPDA: PublicKey,
     authority: PublicKey
   ): Promise<TransactionInstruction> {
     return new TransactionInstruction({
@@ -454,8 +497,19 @@ export class TokenService {
           isWritable: true,
         },
         {
+          pubkey: ruleSetPDA,
+          isSigner: false,
+          isWritable: false,
+        },
+        {
+          // Pays for growing the token info account
           pubkey: authority,
           isSigner: true,
+          isWritable: true,
+        },
+        {
+          pubkey: SystemProgram.programId,
+          isSigner: false,
           isWritable: false,
         },
       ],
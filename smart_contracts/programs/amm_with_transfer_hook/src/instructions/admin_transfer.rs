@@ -0,0 +1,48 @@
+use anchor_lang::prelude::*;
+
+use crate::state::{AdminAcceptedEvent, CustomError, Role, RoleChangedEvent, TokenInfo};
+
+/// Second step of an admin handover: the proposed key signs to take over
+/// as `token_creator`, along with every role the old admin still holds
+#[derive(Accounts)]
+pub struct AcceptAdmin<'info> {
+    #[account(
+        mut,
+        constraint = token_info.pending_token_creator == Some(new_admin.key()) @ CustomError::NotPendingAdmin,
+        seeds = [b"token-info", token_info.token_mint.as_ref()],
+        bump = token_info.bump
+    )]
+    pub token_info: Account<'info, TokenInfo>,
+
+    pub new_admin: Signer<'info>,
+}
+
+impl<'info> AcceptAdmin<'info> {
+    pub fn accept_admin(&mut self) -> Result<()> {
+        let token_info = &mut self.token_info;
+        let old_admin = token_info.token_creator;
+        let new_admin = self.new_admin.key();
+        token_info.token_creator = new_admin;
+        token_info.pending_token_creator = None;
+
+        for role in Role::ALL {
+            if token_info.role_holder(role) == old_admin {
+                token_info.set_role_holder(role, new_admin);
+                emit!(RoleChangedEvent {
+                    token_mint: token_info.token_mint,
+                    role,
+                    old_holder: old_admin,
+                    new_holder: new_admin,
+                    authority: new_admin,
+                });
+            }
+        }
+
+        emit!(AdminAcceptedEvent {
+            token_mint: token_info.token_mint,
+            old_admin,
+            new_admin: token_info.token_creator,
+        });
+        Ok(())
+    }
+}
@@ -0,0 +1,341 @@
+use anchor_lang::prelude::*;
+
+use crate::{
+    instructions::{
+        change_launch_schedule, change_timelock_delay, change_transfer_limit,
+        change_whitelist_mode, rewrite_extra_account_meta_list, set_max_wallet_balance,
+        set_transfer_cooldown, set_transfer_quota, set_whitelist_direction, toggle_rule,
+    },
+    state::{
+        ChangeCancelledEvent, ChangeExecutedEvent, ChangeQueue, ChangeQueuedEvent, CustomError,
+        Lockup, PausedEvent, QueuedChange, Rule, RuleSet, SettingChange, ToggledRule, TokenInfo,
+        UnpausedEvent, MAX_QUEUED_CHANGES, MAX_QUOTA_WINDOW,
+    },
+    util::resize_rule_set,
+};
+
+#[derive(Accounts)]
+pub struct QueueChange<'info> {
+    #[account(
+        seeds = [b"token-info", token_info.token_mint.as_ref()],
+        bump = token_info.bump
+    )]
+    pub token_info: Account<'info, TokenInfo>,
+
+    #[account(
+        init_if_needed,
+        seeds = [b"change-queue", token_info.token_mint.as_ref()],
+        bump,
+        space = ChangeQueue::INIT_SPACE,
+        payer = authority
+    )]
+    pub change_queue: Account<'info, ChangeQueue>,
+
+    /// Must hold the role the queued change requires
+    #[account(mut)]
+    pub authority: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+impl<'info> QueueChange<'info> {
+    /// Queues `change` to become executable once `timelock_delay` seconds
+    /// have passed
+    pub fn queue_change(&mut self, bumps: QueueChangeBumps, change: SettingChange) -> Result<()> {
+        self.token_info
+            .require_role(change.required_role(), &self.authority.key())?;
+        validate_change(&change)?;
+
+        let queue = &mut self.change_queue;
+        require!(queue.changes.len() < MAX_QUEUED_CHANGES, CustomError::ChangeQueueFull);
+
+        queue.token_mint = self.token_info.token_mint;
+        queue.bump = bumps.change_queue;
+
+        let queued = QueuedChange {
+            id: queue.next_id,
+            change: change.clone(),
+            queued_by: self.authority.key(),
+            execute_after: Clock::get()?
+                .unix_timestamp
+                .saturating_add(self.token_info.timelock_delay),
+        };
+        queue.next_id += 1;
+
+        emit!(ChangeQueuedEvent {
+            token_mint: queue.token_mint,
+            change_id: queued.id,
+            change,
+            queued_by: queued.queued_by,
+            execute_after: queued.execute_after,
+        });
+        queue.changes.push(queued);
+        Ok(())
+    }
+}
+
+#[derive(Accounts)]
+pub struct ExecuteChange<'info> {
+    #[account(
+        mut,
+        seeds = [b"token-info", token_info.token_mint.as_ref()],
+        bump = token_info.bump
+    )]
+    pub token_info: Account<'info, TokenInfo>,
+
+    #[account(
+        mut,
+        seeds = [b"change-queue", token_info.token_mint.as_ref()],
+        bump = change_queue.bump
+    )]
+    pub change_queue: Account<'info, ChangeQueue>,
+
+    /// CHECK: ExtraAccountMetaList Account, must use these seeds
+    #[account(
+        mut,
+        seeds = [b"extra-account-metas", token_info.token_mint.as_ref()],
+        bump
+    )]
+    pub extra_account_meta_list: UncheckedAccount<'info>,
+
+    #[account(
+        mut,
+        seeds = [b"rules", token_info.token_mint.as_ref()],
+        bump = rule_set.bump
+    )]
+    pub rule_set: Account<'info, RuleSet>,
+
+    /// Must still hold the change's role, so renouncing also strands
+    /// anything left in the queue. Pays for a larger rule set and meta list.
+    #[account(mut)]
+    pub authority: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+impl<'info> ExecuteChange<'info> {
+    pub fn execute_change(&mut self, change_id: u64) -> Result<()> {
+        let queued = self
+            .change_queue
+            .get(change_id)
+            .cloned()
+            .ok_or(CustomError::QueuedChangeNotFound)?;
+
+        self.token_info
+            .require_role(queued.change.required_role(), &self.authority.key())?;
+        require!(!queued.change.has_own_instruction(), CustomError::ChangeHasOwnInstruction);
+        require!(
+            Clock::get()?.unix_timestamp >= queued.execute_after,
+            CustomError::TimelockNotElapsed
+        );
+
+        self.change_queue.take(change_id);
+        apply_change(
+            &mut self.token_info,
+            &mut self.rule_set,
+            queued.change.clone(),
+            self.authority.key(),
+        )?;
+        resize_rule_set(
+            &self.rule_set,
+            &self.authority.to_account_info(),
+            &self.system_program.to_account_info(),
+        )?;
+        if queued.change.changes_rule_accounts() {
+            rewrite_extra_account_meta_list(
+                &self.extra_account_meta_list.to_account_info(),
+                &self.token_info,
+                &self.rule_set,
+                &self.authority.to_account_info(),
+                &self.system_program.to_account_info(),
+            )?;
+        }
+
+        emit!(ChangeExecutedEvent {
+            token_mint: self.token_info.token_mint,
+            change_id,
+            change: queued.change,
+            executed_by: self.authority.key(),
+        });
+        Ok(())
+    }
+}
+
+#[derive(Accounts)]
+pub struct CancelChange<'info> {
+    #[account(
+        seeds = [b"token-info", token_info.token_mint.as_ref()],
+        bump = token_info.bump
+    )]
+    pub token_info: Account<'info, TokenInfo>,
+
+    #[account(
+        mut,
+        seeds = [b"change-queue", token_info.token_mint.as_ref()],
+        bump = change_queue.bump
+    )]
+    pub change_queue: Account<'info, ChangeQueue>,
+
+    pub authority: Signer<'info>,
+}
+
+impl<'info> CancelChange<'info> {
+    pub fn cancel_change(&mut self, change_id: u64) -> Result<()> {
+        let queued = self
+            .change_queue
+            .get(change_id)
+            .cloned()
+            .ok_or(CustomError::QueuedChangeNotFound)?;
+
+        self.token_info
+            .require_role(queued.change.required_role(), &self.authority.key())?;
+
+        self.change_queue.take(change_id);
+
+        emit!(ChangeCancelledEvent {
+            token_mint: self.token_info.token_mint,
+            change_id,
+            change: queued.change,
+            cancelled_by: self.authority.key(),
+        });
+        Ok(())
+    }
+}
+
+fn validate_change(change: &SettingChange) -> Result<()> {
+    match change {
+        SettingChange::MaxTransferLimit { limit, mode } => {
+            require!(mode.is_valid(*limit), CustomError::InvalidBasisPoints);
+        }
+        SettingChange::MaxTransferLimitSchedule { schedule } => {
+            require!(schedule.is_valid(), CustomError::InvalidLimitSchedule);
+        }
+        SettingChange::TransferQuota { enable, window, .. } => {
+            require!(
+                !*enable || (1..=MAX_QUOTA_WINDOW).contains(window),
+                CustomError::InvalidQuotaWindow
+            );
+        }
+        SettingChange::MaxWalletBalance { amount, mode, .. } => {
+            require!(mode.is_valid(*amount), CustomError::InvalidBasisPoints);
+        }
+        SettingChange::LaunchSchedule { launch_phase_duration, .. } => {
+            require!(*launch_phase_duration >= 0, CustomError::InvalidLaunchSchedule);
+        }
+        SettingChange::Lockup { start_time, cliff_time, end_time, .. } => {
+            require!(
+                Lockup::is_valid_schedule(*start_time, *cliff_time, *end_time),
+                CustomError::InvalidLockupSchedule
+            );
+        }
+        SettingChange::AddRule { rule } => rule.validate()?,
+        SettingChange::TimelockDelay { delay } => {
+            require!(*delay >= 0, CustomError::InvalidTimelockDelay);
+        }
+        _ => {}
+    }
+    Ok(())
+}
+
+/// Same effect and same typed event as the direct instruction for each
+/// setting. Pause transitions also emit `PausedEvent` / `UnpausedEvent`,
+/// which pause watchers rely on.
+fn apply_change(
+    token_info: &mut TokenInfo,
+    rule_set: &mut RuleSet,
+    change: SettingChange,
+    executed_by: Pubkey,
+) -> Result<()> {
+    match change {
+        SettingChange::MaxTransferLimit { limit, mode } => {
+            change_transfer_limit(rule_set, Rule::MaxTransferAmount { limit, mode }, executed_by)?;
+        }
+        SettingChange::MaxTransferLimitSchedule { schedule } => {
+            change_transfer_limit(rule_set, Rule::DecayingTransferLimit { schedule }, executed_by)?;
+        }
+        SettingChange::TransferQuota { enable, quota, window } => {
+            set_transfer_quota(rule_set, enable, quota, window, executed_by)?;
+        }
+        SettingChange::TransferCooldown { enable, interval, unit } => {
+            set_transfer_cooldown(rule_set, enable, interval, unit, executed_by)?;
+        }
+        SettingChange::MaxWalletBalance { enable, amount, mode } => {
+            set_max_wallet_balance(rule_set, enable, amount, mode, executed_by)?;
+        }
+        SettingChange::LaunchSchedule {
+            trading_opens_at,
+            launch_phase_duration,
+            launch_max_transfer_amount,
+            launch_max_wallet_balance,
+        } => change_launch_schedule(
+            token_info,
+            trading_opens_at,
+            launch_phase_duration,
+            launch_max_transfer_amount,
+            launch_max_wallet_balance,
+            executed_by,
+        ),
+        SettingChange::WhitelistEnabled { enable } => {
+            toggle_rule(rule_set, ToggledRule::Whitelist, enable, executed_by)?;
+        }
+        SettingChange::WhitelistMode { mode } => change_whitelist_mode(token_info, mode, executed_by),
+        SettingChange::WhitelistDirection { direction } => {
+            set_whitelist_direction(rule_set, direction, executed_by)?;
+        }
+        SettingChange::DenylistEnabled { enable } => {
+            toggle_rule(rule_set, ToggledRule::Denylist, enable, executed_by)?;
+        }
+        SettingChange::LockupsEnabled { enable } => {
+            toggle_rule(rule_set, ToggledRule::Lockups, enable, executed_by)?;
+        }
+        // Rejected by `execute_change`; see `consume_queued_change`
+        SettingChange::DenylistWallet { .. }
+        | SettingChange::Lockup { .. }
+        | SettingChange::AddRule { .. } => {}
+        SettingChange::Paused { paused } => {
+            if token_info.is_paused == paused {
+                return Ok(());
+            }
+            token_info.is_paused = paused;
+            if paused {
+                emit!(PausedEvent {
+                    token_mint: token_info.token_mint,
+                    pause_authority: executed_by,
+                });
+            } else {
+                emit!(UnpausedEvent {
+                    token_mint: token_info.token_mint,
+                    pause_authority: executed_by,
+                });
+            }
+        }
+        SettingChange::TimelockDelay { delay } => change_timelock_delay(token_info, delay, executed_by),
+    }
+    Ok(())
+}
+
+/// Lets an instruction execute its own queued change; see
+/// `SettingChange::has_own_instruction`. Without the timelock there is nothing to consume.
+pub(crate) fn consume_queued_change(
+    token_info: &TokenInfo,
+    change_queue: Option<&mut Account<ChangeQueue>>,
+    change: SettingChange,
+    executed_by: Pubkey,
+) -> Result<()> {
+    if !token_info.is_timelocked() {
+        return Ok(());
+    }
+
+    let queue = change_queue.ok_or(CustomError::TimelockRequired)?;
+    let queued = queue
+        .take_matured(&change, Clock::get()?.unix_timestamp)
+        .ok_or(CustomError::TimelockRequired)?;
+
+    emit!(ChangeExecutedEvent {
+        token_mint: token_info.token_mint,
+        change_id: queued.id,
+        change,
+        executed_by,
+    });
+    Ok(())
+}
@@ -0,0 +1,100 @@
+use anchor_lang::prelude::*;
+
+use crate::{
+    instructions::change_queue::consume_queued_change,
+    state::{ChangeQueue, DenylistEntry, DenylistUpdatedEvent, SettingChange, TokenInfo},
+};
+
+#[derive(Accounts)]
+#[instruction(wallet: Pubkey)]
+pub struct AddToDenylist<'info> {
+    #[account(
+        has_one = whitelist_manager,
+        seeds = [b"token-info", token_info.token_mint.as_ref()],
+        bump = token_info.bump
+    )]
+    pub token_info: Account<'info, TokenInfo>,
+
+    #[account(
+        init,
+        seeds = [b"denylist", token_info.token_mint.as_ref(), wallet.as_ref()],
+        bump,
+        space = DenylistEntry::INIT_SPACE,
+        payer = whitelist_manager
+    )]
+    pub denylist_entry: Account<'info, DenylistEntry>,
+
+    /// Required while the timelock is on; must hold a matured `DenylistWallet`
+    /// change with the same arguments
+    #[account(
+        mut,
+        seeds = [b"change-queue", token_info.token_mint.as_ref()],
+        bump = change_queue.bump
+    )]
+    pub change_queue: Option<Account<'info, ChangeQueue>>,
+
+    #[account(mut)]
+    pub whitelist_manager: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+impl<'info> AddToDenylist<'info> {
+    pub fn add_to_denylist(&mut self, bumps: AddToDenylistBumps, wallet: Pubkey) -> Result<()> {
+        consume_queued_change(
+            &self.token_info,
+            self.change_queue.as_mut(),
+            SettingChange::DenylistWallet { wallet },
+            self.whitelist_manager.key(),
+        )?;
+
+        self.denylist_entry.set_inner(DenylistEntry {
+            token_mint: self.token_info.token_mint,
+            wallet,
+            bump: bumps.denylist_entry,
+        });
+
+        emit!(DenylistUpdatedEvent {
+            token_mint: self.token_info.token_mint,
+            wallet,
+            was_listed: false,
+            is_listed: true,
+            authority: self.whitelist_manager.key(),
+        });
+        Ok(())
+    }
+}
+
+#[derive(Accounts)]
+pub struct RemoveFromDenylist<'info> {
+    #[account(
+        has_one = whitelist_manager,
+        seeds = [b"token-info", token_info.token_mint.as_ref()],
+        bump = token_info.bump
+    )]
+    pub token_info: Account<'info, TokenInfo>,
+
+    #[account(
+        mut,
+        close = whitelist_manager,
+        seeds = [b"denylist", token_info.token_mint.as_ref(), denylist_entry.wallet.as_ref()],
+        bump = denylist_entry.bump
+    )]
+    pub denylist_entry: Account<'info, DenylistEntry>,
+
+    #[account(mut)]
+    pub whitelist_manager: Signer<'info>,
+}
+
+impl<'info> RemoveFromDenylist<'info> {
+    pub fn remove_from_denylist(&mut self) -> Result<()> {
+        emit!(DenylistUpdatedEvent {
+            token_mint: self.token_info.token_mint,
+            wallet: self.denylist_entry.wallet,
+            was_listed: true,
+            is_listed: false,
+            authority: self.whitelist_manager.key(),
+        });
+        Ok(())
+    }
+}
@@ -1,44 +1,194 @@
 use anchor_lang::prelude::*;
 
-use crate::state::{CustomError, TokenInfo};
+use crate::state::{
+    AdminProposedEvent, AdminRenouncedEvent, AdminTransferCancelledEvent, CustomError,
+    LaunchScheduleChangedEvent, Role, RoleChangedEvent, TimelockDelayChangedEvent, TokenInfo,
+    WhitelistMerkleRootChangedEvent,
+};
 
 
 #[derive(Accounts)]
 pub struct UpdateTokenExtension<'info> {
     #[account(
-        mut, 
-        has_one = token_creator,
+        mut,
         seeds = [b"token-info", token_info.token_mint.as_ref()],
         bump = token_info.bump
     )]
     pub token_info: Account<'info, TokenInfo>,
-    pub token_creator: Signer<'info>,
+    /// Must hold the role each setting requires; see `Role`
+    pub authority: Signer<'info>,
 }
 
 impl<'info> UpdateTokenExtension<'info> {
-    pub fn add_to_whitelist(&mut self, new_address: Pubkey) -> Result<()> {
-        let token_info = &mut self.token_info;
+    /// Holds transfers until `trading_opens_at` (except for launch exemptions)
+    /// and applies the launch limits for `launch_phase_duration` seconds after.
+    /// Goes through the change queue while the timelock is on.
+    pub fn set_launch_schedule(
+        &mut self,
+        trading_opens_at: i64,
+        launch_phase_duration: i64,
+        launch_max_transfer_amount: u64,
+        launch_max_wallet_balance: u64,
+    ) -> Result<()> {
+        self.require_role(Role::LimitsManager)?;
+        require!(!self.token_info.is_timelocked(), CustomError::TimelockRequired);
+        require!(launch_phase_duration >= 0, CustomError::InvalidLaunchSchedule);
 
-        require!(token_info.is_whitelist_enabled, CustomError::WhitelistDisabled);
+        change_launch_schedule(
+            &mut self.token_info,
+            trading_opens_at,
+            launch_phase_duration,
+            launch_max_transfer_amount,
+            launch_max_wallet_balance,
+            self.authority.key(),
+        );
+        Ok(())
+    }
 
-        if !token_info.whitelist_addresses.contains(&new_address) {
-            token_info.whitelist_addresses.push(new_address);
-        }
+    /// Rotating the root only affects new registrations; existing
+    /// `WhitelistEntry` accounts stay valid until removed.
+    pub fn set_whitelist_merkle_root(&mut self, root: [u8; 32]) -> Result<()> {
+        self.require_role(Role::WhitelistManager)?;
 
+        emit!(WhitelistMerkleRootChangedEvent {
+            token_mint: self.token_info.token_mint,
+            old_root: self.token_info.whitelist_merkle_root,
+            new_root: root,
+            authority: self.authority.key(),
+        });
+        self.token_info.whitelist_merkle_root = root;
         Ok(())
     }
 
-    pub fn set_max_transfer_limit(&mut self, limit: u64) -> Result<()> {
-        let token_info = &mut self.token_info;
-        token_info.is_total_transfer_amount_enabled = true;
-        token_info.total_transfer_amount = limit;
+    /// First step of an admin handover; `new_admin` must call `accept_admin`
+    pub fn propose_admin(&mut self, new_admin: Pubkey) -> Result<()> {
+        self.require_admin()?;
+
+        emit!(AdminProposedEvent {
+            token_mint: self.token_info.token_mint,
+            old_pending_admin: self.token_info.pending_token_creator,
+            new_pending_admin: new_admin,
+            authority: self.authority.key(),
+        });
+        self.token_info.pending_token_creator = Some(new_admin);
         Ok(())
     }
 
-    pub fn update_whale_alert(&mut self, enable: bool, amount: u64) -> Result<()> {
+    pub fn cancel_admin_transfer(&mut self) -> Result<()> {
+        self.require_admin()?;
+        let cancelled_admin = self
+            .token_info
+            .pending_token_creator
+            .take()
+            .ok_or(CustomError::NoPendingAdmin)?;
+
+        emit!(AdminTransferCancelledEvent {
+            token_mint: self.token_info.token_mint,
+            cancelled_admin,
+            authority: self.authority.key(),
+        });
+        Ok(())
+    }
+
+    /// Gives up the admin role for good, together with every role. No key can
+    /// sign for the default pubkey, so the rules become immutable.
+    pub fn renounce_admin(&mut self) -> Result<()> {
+        self.require_admin()?;
+        // Nobody could unpause afterwards
+        require!(!self.token_info.is_paused, CustomError::RenounceWhilePaused);
         let token_info = &mut self.token_info;
-        token_info.is_whale_enabled = enable;
-        token_info.whale_amount = amount;
+        token_info.token_creator = Pubkey::default();
+        token_info.pending_token_creator = None;
+        for role in Role::ALL {
+            token_info.set_role_holder(role, Pubkey::default());
+        }
+
+        emit!(AdminRenouncedEvent {
+            token_mint: token_info.token_mint,
+            old_admin: self.authority.key(),
+        });
+        Ok(())
+    }
+
+    /// Raising the delay (or turning the timelock on) is immediate; lowering
+    /// it has to be queued as `SettingChange::TimelockDelay`
+    pub fn set_timelock_delay(&mut self, delay: i64) -> Result<()> {
+        self.require_role(Role::RoleAdmin)?;
+        require!(delay >= 0, CustomError::InvalidTimelockDelay);
+        require!(delay >= self.token_info.timelock_delay, CustomError::TimelockRequired);
+
+        change_timelock_delay(&mut self.token_info, delay, self.authority.key());
         Ok(())
     }
-}
\ No newline at end of file
+
+    /// Hands `role` to `holder`; only the role admin may do this
+    pub fn set_role(&mut self, role: Role, holder: Pubkey) -> Result<()> {
+        self.require_role(Role::RoleAdmin)?;
+
+        emit!(RoleChangedEvent {
+            token_mint: self.token_info.token_mint,
+            role,
+            old_holder: self.token_info.role_holder(role),
+            new_holder: holder,
+            authority: self.authority.key(),
+        });
+        self.token_info.set_role_holder(role, holder);
+        Ok(())
+    }
+
+    /// Same as `set_role(Role::Pauser, pause_authority)`; the pause authority
+    /// is the pauser role
+    pub fn set_pause_authority(&mut self, pause_authority: Pubkey) -> Result<()> {
+        self.set_role(Role::Pauser, pause_authority)
+    }
+
+    fn require_role(&self, role: Role) -> Result<()> {
+        self.token_info.require_role(role, &self.authority.key())
+    }
+
+    fn require_admin(&self) -> Result<()> {
+        require_keys_eq!(
+            self.token_info.token_creator,
+            self.authority.key(),
+            CustomError::MissingRole
+        );
+        Ok(())
+    }
+}
+
+pub(crate) fn change_launch_schedule(
+    token_info: &mut TokenInfo,
+    trading_opens_at: i64,
+    launch_phase_duration: i64,
+    launch_max_transfer_amount: u64,
+    launch_max_wallet_balance: u64,
+    authority: Pubkey,
+) {
+    emit!(LaunchScheduleChangedEvent {
+        token_mint: token_info.token_mint,
+        old_trading_opens_at: token_info.trading_opens_at,
+        new_trading_opens_at: trading_opens_at,
+        old_launch_phase_duration: token_info.launch_phase_duration,
+        new_launch_phase_duration: launch_phase_duration,
+        old_launch_max_transfer_amount: token_info.launch_max_transfer_amount,
+        new_launch_max_transfer_amount: launch_max_transfer_amount,
+        old_launch_max_wallet_balance: token_info.launch_max_wallet_balance,
+        new_launch_max_wallet_balance: launch_max_wallet_balance,
+        authority,
+    });
+
+    token_info.trading_opens_at = trading_opens_at;
+    token_info.launch_phase_duration = launch_phase_duration;
+    token_info.launch_max_transfer_amount = launch_max_transfer_amount;
+    token_info.launch_max_wallet_balance = launch_max_wallet_balance;
+}
+
+pub(crate) fn change_timelock_delay(token_info: &mut TokenInfo, delay: i64, authority: Pubkey) {
+    emit!(TimelockDelayChangedEvent {
+        token_mint: token_info.token_mint,
+        old_delay: token_info.timelock_delay,
+        new_delay: delay,
+        authority,
+    });
+    token_info.timelock_delay = delay;
+}
@@ -0,0 +1,98 @@
+use anchor_lang::prelude::*;
+
+use crate::{
+    state::{CustomError, ExemptionList, ExemptionUpdatedEvent, TokenInfo},
+    util::{insert_capped, remove_address, resize_token_info},
+};
+
+#[derive(Accounts)]
+pub struct ExemptionOps<'info> {
+    #[account(
+        mut,
+        seeds = [b"token-info", token_info.token_mint.as_ref()],
+        bump = token_info.bump
+    )]
+    pub token_info: Account<'info, TokenInfo>,
+
+    /// Holder of the list's `ExemptionList::manager` role
+    #[account(mut)]
+    pub authority: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+impl<'info> ExemptionOps<'info> {
+    /// `address` may be a token account (e.g. a pool vault) or a wallet that
+    /// owns token accounts (e.g. a treasury)
+    pub fn add_balance_cap_exemption(&mut self, address: Pubkey) -> Result<()> {
+        self.add_exemption(ExemptionList::BalanceCap, address)
+    }
+
+    pub fn remove_balance_cap_exemption(&mut self, address: Pubkey) -> Result<()> {
+        self.remove_exemption(ExemptionList::BalanceCap, address)
+    }
+
+    /// `wallet` may send before trading opens and is not bound by launch
+    /// phase limits
+    pub fn add_launch_exemption(&mut self, wallet: Pubkey) -> Result<()> {
+        self.add_exemption(ExemptionList::Launch, wallet)
+    }
+
+    pub fn remove_launch_exemption(&mut self, wallet: Pubkey) -> Result<()> {
+        self.remove_exemption(ExemptionList::Launch, wallet)
+    }
+
+    /// Recovery `wallet` may still send or receive while transfers are paused
+    pub fn add_pause_exemption(&mut self, wallet: Pubkey) -> Result<()> {
+        self.add_exemption(ExemptionList::Pause, wallet)
+    }
+
+    pub fn remove_pause_exemption(&mut self, wallet: Pubkey) -> Result<()> {
+        self.remove_exemption(ExemptionList::Pause, wallet)
+    }
+
+    fn add_exemption(&mut self, list: ExemptionList, address: Pubkey) -> Result<()> {
+        self.token_info.require_role(list.manager(), &self.authority.key())?;
+
+        let exemptions = self.token_info.exemptions_mut(list);
+        let inserted = insert_capped(exemptions, address, list.capacity(), CustomError::ExemptionListFull)?;
+        if inserted {
+            self.resize_token_info()?;
+        }
+
+        self.emit_updated(list, address, !inserted, true);
+        Ok(())
+    }
+
+    fn remove_exemption(&mut self, list: ExemptionList, address: Pubkey) -> Result<()> {
+        self.token_info.require_role(list.manager(), &self.authority.key())?;
+
+        let removed = remove_address(self.token_info.exemptions_mut(list), &address);
+        if removed {
+            self.resize_token_info()?;
+        }
+
+        self.emit_updated(list, address, removed, false);
+        Ok(())
+    }
+
+    fn emit_updated(&self, list: ExemptionList, address: Pubkey, was_exempt: bool, is_exempt: bool) {
+        emit!(ExemptionUpdatedEvent {
+            token_mint: self.token_info.token_mint,
+            list,
+            address,
+            was_exempt,
+            is_exempt,
+            list_len: self.token_info.exemptions(list).len() as u32,
+            authority: self.authority.key(),
+        });
+    }
+
+    fn resize_token_info(&self) -> Result<()> {
+        resize_token_info(
+            &self.token_info,
+            &self.authority.to_account_info(),
+            &self.system_program.to_account_info(),
+        )
+    }
+}
@@ -1,58 +1,232 @@
 use anchor_lang::prelude::*;
-use anchor_spl::token_interface::Mint;
+use anchor_spl::{
+    token_2022::spl_token_2022::extension::transfer_hook::TransferHook,
+    token_interface::{get_mint_extension_data, Mint},
+};
 use spl_tlv_account_resolution::{
     account::ExtraAccountMeta, 
     seeds::Seed, 
     state::ExtraAccountMetaList
 };
+use spl_transfer_hook_interface::instruction::ExecuteInstruction;
 
-use crate::state::TokenInfo;
+use crate::{
+    state::{CustomError, Rule, RuleSet, ThresholdMode, TokenInfo, WhitelistDirection, WhitelistMode},
+    util::resize_rule_set,
+};
 
 #[derive(Accounts)]
 pub struct InitializeExtraAccountMetaList<'info> {
     #[account(mut)]
     payer: Signer<'info>,
 
+    /// Mint authority or TransferHook extension authority of `mint`;
+    /// becomes the `token_creator` and starts out holding every role
+    pub authority: Signer<'info>,
+
     /// CHECK: ExtraAccountMetaList Account, must use these seeds
     #[account(
         init,
         seeds = [b"extra-account-metas", mint.key().as_ref()],
         bump,
         space = ExtraAccountMetaList::size_of(
-            InitializeExtraAccountMetaList::extra_account_metas()?.len()
+            InitializeExtraAccountMetaList::BASE_EXTRA_ACCOUNT_METAS
         )?,
         payer = payer
     )]
     pub extra_account_meta_list: AccountInfo<'info>,
     #[account(
-        init_if_needed,
+        init,
         seeds = [b"token-info", mint.key().as_ref()],
         bump,
         space = TokenInfo::INIT_SPACE,
         payer = payer
     )]
     pub token_info: Account<'info, TokenInfo>,
+    #[account(
+        init,
+        seeds = [b"rules", mint.key().as_ref()],
+        bump,
+        space = RuleSet::INIT_SPACE,
+        payer = payer
+    )]
+    pub rule_set: Account<'info, RuleSet>,
+    #[account(
+        extensions::transfer_hook::program_id = crate::ID,
+    )]
     pub mint: InterfaceAccount<'info, Mint>,
     pub system_program: Program<'info, System>,
 }
 
 impl<'info> InitializeExtraAccountMetaList<'info> {
-    pub fn extra_account_metas() -> Result<Vec<ExtraAccountMeta>> {
-        Ok(
-            vec![
-                ExtraAccountMeta::new_with_seeds(
+    /// A fresh mint only needs `token_info` and `rule_set` resolved, since
+    /// its initial rules read no other account; rule accounts are added by
+    /// the instructions that enable them.
+    pub const BASE_EXTRA_ACCOUNT_METAS: usize = 2;
+
+    /// Extra accounts appended to every `Execute` call: `token_info` and
+    /// `rule_set` first, which `TransferHook` declares, then the accounts the
+    /// rules read, which it takes from the remaining accounts.
+    pub fn extra_account_metas(
+        token_info: &TokenInfo,
+        rule_set: &RuleSet,
+    ) -> Result<Vec<ExtraAccountMeta>> {
+        let mut metas = vec![
+            // token_info: ["token-info", mint]
+            ExtraAccountMeta::new_with_seeds(
+                &[
+                    Seed::Literal {
+                        bytes: b"token-info".to_vec(),
+                    },
+                    Seed::AccountKey { index: 1 },
+                ],
+                false, // is_signer
+                false // is_writable
+            )?,
+            // rule_set: ["rules", mint]
+            ExtraAccountMeta::new_with_seeds(
+                &[
+                    Seed::Literal {
+                        bytes: b"rules".to_vec(),
+                    },
+                    Seed::AccountKey { index: 1 },
+                ],
+                false, // is_signer
+                false // is_writable
+            )?,
+        ];
+
+        let (whitelists_source, whitelists_destination) = rule_set.whitelisted_sides();
+        if token_info.uses_whitelist_entries() {
+            if whitelists_source {
+                // source owner's whitelist entry: ["whitelist", mint, source_token.owner]
+                metas.push(ExtraAccountMeta::new_with_seeds(
+                    &[
+                        Seed::Literal {
+                            bytes: b"whitelist".to_vec(),
+                        },
+                        Seed::AccountKey { index: 1 },
+                        Seed::AccountData {
+                            account_index: 0,
+                            data_index: 32,
+                            length: 32,
+                        },
+                    ],
+                    false, // is_signer
+                    false // is_writable
+                )?);
+            }
+            if whitelists_destination {
+                // destination owner's whitelist entry: ["whitelist", mint, destination_token.owner]
+                metas.push(ExtraAccountMeta::new_with_seeds(
                     &[
                         Seed::Literal {
                             bytes: b"whitelist".to_vec(),
                         },
+                        Seed::AccountKey { index: 1 },
+                        Seed::AccountData {
+                            account_index: 2,
+                            data_index: 32,
+                            length: 32,
+                        },
                     ],
                     false, // is_signer
                     false // is_writable
-                )?
-            ]
-        )
+                )?);
+            }
+        }
+
+        if rule_set.rules.contains(&Rule::Denylist) {
+            // source owner's denylist entry: ["denylist", mint, source_token.owner]
+            metas.push(ExtraAccountMeta::new_with_seeds(
+                &[
+                    Seed::Literal {
+                        bytes: b"denylist".to_vec(),
+                    },
+                    Seed::AccountKey { index: 1 },
+                    Seed::AccountData {
+                        account_index: 0,
+                        data_index: 32,
+                        length: 32,
+                    },
+                ],
+                false, // is_signer
+                false // is_writable
+            )?);
+            // destination owner's denylist entry: ["denylist", mint, destination_token.owner]
+            metas.push(ExtraAccountMeta::new_with_seeds(
+                &[
+                    Seed::Literal {
+                        bytes: b"denylist".to_vec(),
+                    },
+                    Seed::AccountKey { index: 1 },
+                    Seed::AccountData {
+                        account_index: 2,
+                        data_index: 32,
+                        length: 32,
+                    },
+                ],
+                false, // is_signer
+                false // is_writable
+            )?);
+        }
+
+        if rule_set.uses_wallet_state() {
+            // source owner's wallet state: ["wallet-state", mint, source_token.owner]
+            metas.push(ExtraAccountMeta::new_with_seeds(
+                &[
+                    Seed::Literal {
+                        bytes: b"wallet-state".to_vec(),
+                    },
+                    Seed::AccountKey { index: 1 },
+                    Seed::AccountData {
+                        account_index: 0,
+                        data_index: 32,
+                        length: 32,
+                    },
+                ],
+                false, // is_signer
+                true // is_writable
+            )?);
+        }
+
+        if rule_set.rules.contains(&Rule::Lockups) {
+            // source owner's lockup: ["lockup", mint, source_token.owner]
+            metas.push(ExtraAccountMeta::new_with_seeds(
+                &[
+                    Seed::Literal {
+                        bytes: b"lockup".to_vec(),
+                    },
+                    Seed::AccountKey { index: 1 },
+                    Seed::AccountData {
+                        account_index: 0,
+                        data_index: 32,
+                        length: 32,
+                    },
+                ],
+                false, // is_signer
+                false // is_writable
+            )?);
+        }
+
+        if token_info.has_transfer_stats {
+            // transfer statistics: ["stats", mint]
+            metas.push(ExtraAccountMeta::new_with_seeds(
+                &[
+                    Seed::Literal {
+                        bytes: b"stats".to_vec(),
+                    },
+                    Seed::AccountKey { index: 1 },
+                ],
+                false, // is_signer
+                true // is_writable
+            )?);
+        }
+
+        Ok(metas)
     }
 
+    #[allow(clippy::too_many_arguments)]
     pub fn initialize_token_info(
         &mut self, 
         bumps: InitializeExtraAccountMetaListBumps,
@@ -65,24 +239,117 @@ impl<'info> InitializeExtraAccountMetaList<'info> {
         whale_amount: u64,
         total_transfer_amount: u64,
     ) -> Result<()> {
-        
+        self.check_authority()?;
+
         self.token_info.set_inner(TokenInfo {
             token_address: self.mint.key(),
-            token_name: token_name,
-            token_symbol: token_symbol,
+            token_name,
+            token_symbol,
             token_decimals: self.mint.decimals,
-            token_total_supply: token_total_supply,
+            token_total_supply,
             token_mint: self.mint.key(),
-            token_creator: self.payer.key(),
-            is_whale_enabled,
-            is_whitelist_enabled,
-            is_total_transfer_amount_enabled,
-            whale_amount,
-            total_transfer_amount,
+            token_creator: self.authority.key(),
+            legacy_whale_enabled: false,
+            legacy_whitelist_enabled: false,
+            legacy_transfer_limit_enabled: false,
+            legacy_whale_amount: 0,
+            legacy_transfer_limit: 0,
             whitelist_addresses: vec![],
             bump: bumps.token_info,
+            whitelist_mode: WhitelistMode::List,
+            whitelist_merkle_root: [0; 32],
+            balance_cap_exemptions: vec![],
+            trading_opens_at: 0,
+            launch_phase_duration: 0,
+            launch_max_transfer_amount: 0,
+            launch_max_wallet_balance: 0,
+            launch_exemptions: vec![],
+            pause_authority: self.authority.key(),
+            is_paused: false,
+            pause_exemptions: vec![],
+            pending_token_creator: None,
+            whitelist_manager: self.authority.key(),
+            limits_manager: self.authority.key(),
+            role_admin: self.authority.key(),
+            timelock_delay: 0,
+            has_transfer_stats: false,
+            version: TokenInfo::VERSION,
+        });
+
+        self.rule_set.set_inner(RuleSet {
+            token_mint: self.mint.key(),
+            bump: bumps.rule_set,
+            rules: initial_rules(
+                is_whale_enabled,
+                is_whitelist_enabled,
+                is_total_transfer_amount_enabled,
+                whale_amount,
+                total_transfer_amount,
+            ),
         });
+        for rule in &self.rule_set.rules {
+            rule.validate()?;
+        }
+        resize_rule_set(
+            &self.rule_set,
+            &self.payer.to_account_info(),
+            &self.system_program.to_account_info(),
+        )?;
+
+        ExtraAccountMetaList::init::<ExecuteInstruction>(
+            &mut self.extra_account_meta_list.try_borrow_mut_data()?,
+            &Self::extra_account_metas(&self.token_info, &self.rule_set)?,
+        )?;
+
+        Ok(())
+    }
+
+    /// Only the mint authority or the TransferHook extension authority may
+    /// claim the rule admin role for a mint.
+    fn check_authority(&self) -> Result<()> {
+        let authority = self.authority.key();
+
+        let is_mint_authority = self.mint.mint_authority == Some(authority).into();
+
+        let transfer_hook = get_mint_extension_data::<TransferHook>(&self.mint.to_account_info())?;
+        let is_hook_authority = Option::<Pubkey>::from(transfer_hook.authority) == Some(authority);
+
+        require!(
+            is_mint_authority || is_hook_authority,
+            CustomError::UnauthorizedInitializer
+        );
 
         Ok(())
     }
-}
\ No newline at end of file
+}
+
+/// Rules for the feature switches `initialize_token_info` takes, in the
+/// order the hook used to check them. `migrate_token_info` builds the same
+/// rules from the legacy `TokenInfo` settings.
+pub(crate) fn initial_rules(
+    is_whale_enabled: bool,
+    is_whitelist_enabled: bool,
+    is_total_transfer_amount_enabled: bool,
+    whale_amount: u64,
+    total_transfer_amount: u64,
+) -> Vec<Rule> {
+    let mut rules = Vec::new();
+    if is_whitelist_enabled {
+        rules.push(Rule::Whitelist {
+            direction: WhitelistDirection::Source,
+        });
+    }
+    if is_total_transfer_amount_enabled {
+        rules.push(Rule::MaxTransferAmount {
+            limit: total_transfer_amount,
+            mode: ThresholdMode::Absolute,
+        });
+    }
+    if is_whale_enabled {
+        rules.push(Rule::WhaleAlert {
+            thresholds: vec![whale_amount],
+            mode: ThresholdMode::Absolute,
+        });
+    }
+    rules
+}
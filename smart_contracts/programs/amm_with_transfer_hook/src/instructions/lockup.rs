@@ -0,0 +1,126 @@
+use anchor_lang::prelude::*;
+
+use crate::{
+    instructions::change_queue::consume_queued_change,
+    state::{
+        ChangeQueue, CustomError, Lockup, LockupClosedEvent, LockupCreatedEvent, SettingChange,
+        TokenInfo,
+    },
+};
+
+#[derive(Accounts)]
+#[instruction(wallet: Pubkey)]
+pub struct CreateLockup<'info> {
+    #[account(
+        has_one = limits_manager,
+        seeds = [b"token-info", token_info.token_mint.as_ref()],
+        bump = token_info.bump
+    )]
+    pub token_info: Account<'info, TokenInfo>,
+
+    #[account(
+        init,
+        seeds = [b"lockup", token_info.token_mint.as_ref(), wallet.as_ref()],
+        bump,
+        space = Lockup::INIT_SPACE,
+        payer = limits_manager
+    )]
+    pub lockup: Account<'info, Lockup>,
+
+    /// Required while the timelock is on; must hold a matured `Lockup`
+    /// change with the same arguments
+    #[account(
+        mut,
+        seeds = [b"change-queue", token_info.token_mint.as_ref()],
+        bump = change_queue.bump
+    )]
+    pub change_queue: Option<Account<'info, ChangeQueue>>,
+
+    #[account(mut)]
+    pub limits_manager: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+impl<'info> CreateLockup<'info> {
+    /// The hook compares the locked amount with the balance of the token
+    /// account being debited, not with everything `wallet` holds, so the
+    /// allocation must stay in a single token account. Any other token
+    /// account of `wallet` is held to the full locked amount as well.
+    pub fn create_lockup(
+        &mut self,
+        bumps: CreateLockupBumps,
+        wallet: Pubkey,
+        total_locked: u64,
+        start_time: i64,
+        cliff_time: i64,
+        end_time: i64,
+    ) -> Result<()> {
+        require!(
+            Lockup::is_valid_schedule(start_time, cliff_time, end_time),
+            CustomError::InvalidLockupSchedule
+        );
+        consume_queued_change(
+            &self.token_info,
+            self.change_queue.as_mut(),
+            SettingChange::Lockup { wallet, total_locked, start_time, cliff_time, end_time },
+            self.limits_manager.key(),
+        )?;
+
+        self.lockup.set_inner(Lockup {
+            token_mint: self.token_info.token_mint,
+            wallet,
+            total_locked,
+            start_time,
+            cliff_time,
+            end_time,
+            bump: bumps.lockup,
+        });
+
+        emit!(LockupCreatedEvent {
+            token_mint: self.token_info.token_mint,
+            wallet,
+            total_locked,
+            start_time,
+            cliff_time,
+            end_time,
+            authority: self.limits_manager.key(),
+        });
+        Ok(())
+    }
+}
+
+/// Closing a lockup releases whatever is still locked
+#[derive(Accounts)]
+pub struct CloseLockup<'info> {
+    #[account(
+        has_one = limits_manager,
+        seeds = [b"token-info", token_info.token_mint.as_ref()],
+        bump = token_info.bump
+    )]
+    pub token_info: Account<'info, TokenInfo>,
+
+    #[account(
+        mut,
+        close = limits_manager,
+        seeds = [b"lockup", token_info.token_mint.as_ref(), lockup.wallet.as_ref()],
+        bump = lockup.bump
+    )]
+    pub lockup: Account<'info, Lockup>,
+
+    #[account(mut)]
+    pub limits_manager: Signer<'info>,
+}
+
+impl<'info> CloseLockup<'info> {
+    pub fn close_lockup(&mut self) -> Result<()> {
+        emit!(LockupClosedEvent {
+            token_mint: self.token_info.token_mint,
+            wallet: self.lockup.wallet,
+            total_locked: self.lockup.total_locked,
+            released_amount: self.lockup.locked_amount(Clock::get()?.unix_timestamp),
+            authority: self.limits_manager.key(),
+        });
+        Ok(())
+    }
+}
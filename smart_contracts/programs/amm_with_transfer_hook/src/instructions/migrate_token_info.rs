@@ -0,0 +1,104 @@
+use anchor_lang::prelude::*;
+use anchor_spl::token_interface::Mint;
+
+use crate::{
+    instructions::{initial_rules, rewrite_extra_account_meta_list},
+    state::{CustomError, Role, RuleSet, TokenInfo},
+    util::{resize_account, resize_rule_set},
+};
+
+#[derive(Accounts)]
+pub struct MigrateTokenInfo<'info> {
+    /// CHECK: `TokenInfo` written by an older program version. It is grown
+    /// before it is deserialized, since the old layout may be too short.
+    #[account(
+        mut,
+        owner = crate::ID,
+        seeds = [b"token-info", mint.key().as_ref()],
+        bump
+    )]
+    pub token_info: UncheckedAccount<'info>,
+
+    #[account(
+        init_if_needed,
+        seeds = [b"rules", mint.key().as_ref()],
+        bump,
+        space = RuleSet::INIT_SPACE,
+        payer = token_creator
+    )]
+    pub rule_set: Account<'info, RuleSet>,
+
+    /// CHECK: ExtraAccountMetaList Account, must use these seeds
+    #[account(
+        mut,
+        seeds = [b"extra-account-metas", mint.key().as_ref()],
+        bump
+    )]
+    pub extra_account_meta_list: UncheckedAccount<'info>,
+
+    pub mint: InterfaceAccount<'info, Mint>,
+
+    #[account(mut)]
+    pub token_creator: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+impl<'info> MigrateTokenInfo<'info> {
+    /// Upgrades a `TokenInfo` written before roles and the rule set existed:
+    /// every role, read as the default pubkey from the old account's slack,
+    /// goes to `token_creator`, and the legacy whitelist, transfer limit and
+    /// whale settings become the mint's rules.
+    pub fn migrate_token_info(&mut self, bumps: MigrateTokenInfoBumps) -> Result<()> {
+        let account = self.token_info.to_account_info();
+        let payer = self.token_creator.to_account_info();
+        let system_program = self.system_program.to_account_info();
+
+        let size = account.data_len().max(TokenInfo::INIT_SPACE);
+        resize_account(&account, &payer, &system_program, size)?;
+
+        let mut token_info = TokenInfo::try_deserialize(&mut &account.try_borrow_data()?[..])?;
+        require!(token_info.version < TokenInfo::VERSION, CustomError::AlreadyMigrated);
+        require_keys_eq!(
+            token_info.token_creator,
+            self.token_creator.key(),
+            CustomError::MissingRole
+        );
+
+        let token_creator = token_info.token_creator;
+        for role in Role::ALL {
+            if token_info.role_holder(role) == Pubkey::default() {
+                token_info.set_role_holder(role, token_creator);
+            }
+        }
+
+        self.rule_set.set_inner(RuleSet {
+            token_mint: token_info.token_mint,
+            bump: bumps.rule_set,
+            rules: initial_rules(
+                token_info.legacy_whale_enabled,
+                token_info.legacy_whitelist_enabled,
+                token_info.legacy_transfer_limit_enabled,
+                token_info.legacy_whale_amount,
+                token_info.legacy_transfer_limit,
+            ),
+        });
+        token_info.legacy_whale_enabled = false;
+        token_info.legacy_whitelist_enabled = false;
+        token_info.legacy_transfer_limit_enabled = false;
+        token_info.legacy_whale_amount = 0;
+        token_info.legacy_transfer_limit = 0;
+        token_info.version = TokenInfo::VERSION;
+        token_info.try_serialize(&mut &mut account.try_borrow_mut_data()?[..])?;
+
+        resize_account(&account, &payer, &system_program, token_info.current_space()?)?;
+        resize_rule_set(&self.rule_set, &payer, &system_program)?;
+        rewrite_extra_account_meta_list(
+            &self.extra_account_meta_list.to_account_info(),
+            &token_info,
+            &self.rule_set,
+            &payer,
+            &system_program,
+        )
+    }
+}
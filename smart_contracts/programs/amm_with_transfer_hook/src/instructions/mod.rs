@@ -1,9 +1,35 @@
 pub mod init_extra_account_meta;
+pub mod update_extra_account_meta;
+pub mod migrate_token_info;
 pub mod whitelist_operations;
+pub mod whitelist_entry;
+pub mod denylist_entry;
+pub mod wallet_state;
+pub mod exemptions;
+pub mod lockup;
+pub mod pause;
+pub mod admin_transfer;
 pub mod edit_token_settings;
+pub mod rule_settings;
+pub mod change_queue;
+pub mod rule_set;
+pub mod transfer_stats;
 pub mod transfer_hook;
 
 pub use init_extra_account_meta::*;
+pub use update_extra_account_meta::*;
+pub use migrate_token_info::*;
 pub use whitelist_operations::*;
+pub use whitelist_entry::*;
+pub use denylist_entry::*;
+pub use wallet_state::*;
+pub use exemptions::*;
+pub use lockup::*;
+pub use pause::*;
+pub use admin_transfer::*;
 pub use edit_token_settings::*;
+pub use rule_settings::*;
+pub use change_queue::*;
+pub use rule_set::*;
+pub use transfer_stats::*;
 pub use transfer_hook::*;
\ No newline at end of file
@@ -0,0 +1,42 @@
+use anchor_lang::prelude::*;
+
+use crate::state::{CustomError, PausedEvent, TokenInfo, UnpausedEvent};
+
+#[derive(Accounts)]
+pub struct PauseOps<'info> {
+    #[account(
+        mut,
+        has_one = pause_authority,
+        seeds = [b"token-info", token_info.token_mint.as_ref()],
+        bump = token_info.bump
+    )]
+    pub token_info: Account<'info, TokenInfo>,
+
+    pub pause_authority: Signer<'info>,
+}
+
+impl<'info> PauseOps<'info> {
+    /// Stays immediate while the timelock is on: pausing only halts
+    /// transfers and cannot move anyone's tokens
+    pub fn pause(&mut self) -> Result<()> {
+        require!(!self.token_info.is_paused, CustomError::AlreadyPaused);
+        self.token_info.is_paused = true;
+
+        emit!(PausedEvent {
+            token_mint: self.token_info.token_mint,
+            pause_authority: self.pause_authority.key(),
+        });
+        Ok(())
+    }
+
+    pub fn unpause(&mut self) -> Result<()> {
+        require!(self.token_info.is_paused, CustomError::NotPaused);
+        self.token_info.is_paused = false;
+
+        emit!(UnpausedEvent {
+            token_mint: self.token_info.token_mint,
+            pause_authority: self.pause_authority.key(),
+        });
+        Ok(())
+    }
+}
@@ -0,0 +1,146 @@
+use anchor_lang::prelude::*;
+
+use crate::{
+    instructions::{change_queue::consume_queued_change, rewrite_extra_account_meta_list},
+    state::{
+        ChangeQueue, CustomError, Rule, RuleAddedEvent, RuleMovedEvent, RuleRemovedEvent, RuleSet,
+        SettingChange, TokenInfo, MAX_RULES,
+    },
+    util::resize_rule_set,
+};
+
+#[derive(Accounts)]
+pub struct RuleSetOps<'info> {
+    #[account(
+        seeds = [b"token-info", token_info.token_mint.as_ref()],
+        bump = token_info.bump
+    )]
+    pub token_info: Account<'info, TokenInfo>,
+
+    #[account(
+        mut,
+        seeds = [b"rules", token_info.token_mint.as_ref()],
+        bump = rule_set.bump
+    )]
+    pub rule_set: Account<'info, RuleSet>,
+
+    /// CHECK: ExtraAccountMetaList Account, must use these seeds
+    #[account(
+        mut,
+        seeds = [b"extra-account-metas", token_info.token_mint.as_ref()],
+        bump
+    )]
+    pub extra_account_meta_list: UncheckedAccount<'info>,
+
+    /// Required to add a rule that restricts transfers while the timelock is
+    /// on; must hold a matured `AddRule` change with the same rule
+    #[account(
+        mut,
+        seeds = [b"change-queue", token_info.token_mint.as_ref()],
+        bump = change_queue.bump
+    )]
+    pub change_queue: Option<Account<'info, ChangeQueue>>,
+
+    /// Must hold the role of the rule being changed; see `Rule::required_role`
+    #[account(mut)]
+    pub authority: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+impl<'info> RuleSetOps<'info> {
+    /// Appends `rule`, evaluated after the existing ones. While the timelock
+    /// is on, a rule that restricts transfers must be queued as
+    /// `SettingChange::AddRule` first.
+    pub fn add_rule(&mut self, rule: Rule) -> Result<()> {
+        self.require_role(&rule)?;
+        rule.validate()?;
+        require!(self.rule_set.rules.len() < MAX_RULES, CustomError::RuleSetFull);
+        if rule.restricts_transfers() {
+            consume_queued_change(
+                &self.token_info,
+                self.change_queue.as_mut(),
+                SettingChange::AddRule { rule: rule.clone() },
+                self.authority.key(),
+            )?;
+        }
+
+        emit!(RuleAddedEvent {
+            token_mint: self.token_info.token_mint,
+            index: self.rule_set.rules.len() as u32,
+            rule: rule.clone(),
+            authority: self.authority.key(),
+        });
+        self.rule_set.rules.push(rule);
+        resize_rule_set(
+            &self.rule_set,
+            &self.authority.to_account_info(),
+            &self.system_program.to_account_info(),
+        )?;
+        self.rewrite_extra_account_meta_list()
+    }
+
+    pub fn remove_rule(&mut self, index: u32) -> Result<()> {
+        let rule = self.rule_at(index)?.clone();
+        self.require_role(&rule)?;
+
+        self.rule_set.rules.remove(index as usize);
+        emit!(RuleRemovedEvent {
+            token_mint: self.token_info.token_mint,
+            index,
+            rule,
+            authority: self.authority.key(),
+        });
+        resize_rule_set(
+            &self.rule_set,
+            &self.authority.to_account_info(),
+            &self.system_program.to_account_info(),
+        )?;
+        self.rewrite_extra_account_meta_list()
+    }
+
+    /// Moves the rule at `from` so it is evaluated at position `to`
+    pub fn move_rule(&mut self, from: u32, to: u32) -> Result<()> {
+        let rule = self.rule_at(from)?.clone();
+        self.require_role(&rule)?;
+        require!(
+            (to as usize) < self.rule_set.rules.len(),
+            CustomError::RuleIndexOutOfBounds
+        );
+
+        let rule = self.rule_set.rules.remove(from as usize);
+        self.rule_set.rules.insert(to as usize, rule.clone());
+
+        emit!(RuleMovedEvent {
+            token_mint: self.token_info.token_mint,
+            old_index: from,
+            new_index: to,
+            rule,
+            authority: self.authority.key(),
+        });
+        Ok(())
+    }
+
+    fn rule_at(&self, index: u32) -> Result<&Rule> {
+        self.rule_set
+            .rules
+            .get(index as usize)
+            .ok_or_else(|| error!(CustomError::RuleIndexOutOfBounds))
+    }
+
+    fn require_role(&self, rule: &Rule) -> Result<()> {
+        self.token_info
+            .require_role(rule.required_role(), &self.authority.key())
+    }
+
+    /// A `Cooldown` rule adds the owner's `WalletState` to the rule accounts
+    fn rewrite_extra_account_meta_list(&self) -> Result<()> {
+        rewrite_extra_account_meta_list(
+            &self.extra_account_meta_list.to_account_info(),
+            &self.token_info,
+            &self.rule_set,
+            &self.authority.to_account_info(),
+            &self.system_program.to_account_info(),
+        )
+    }
+}
@@ -0,0 +1,505 @@
+use anchor_lang::prelude::*;
+
+use crate::{
+    instructions::rewrite_extra_account_meta_list,
+    state::{
+        CooldownChangedEvent, CooldownUnit, CustomError, MaxWalletBalanceChangedEvent, Role, Rule,
+        RuleSet, RuleToggledEvent, ThresholdMode, ToggledRule, TokenInfo,
+        TransferLimitChangedEvent, TransferLimitSchedule, TransferQuotaChangedEvent,
+        WhaleAlertChangedEvent, WhitelistDirection, WhitelistDirectionChangedEvent, WhitelistMode,
+        WhitelistModeChangedEvent,
+    },
+    util::resize_rule_set,
+};
+
+/// Settings of the built-in rules, kept as `Rule`s in the mint's `RuleSet`.
+/// Each one resizes the rule set and rewrites the extra account meta list in
+/// the same instruction.
+#[derive(Accounts)]
+pub struct UpdateRuleSettings<'info> {
+    #[account(
+        mut,
+        seeds = [b"token-info", token_info.token_mint.as_ref()],
+        bump = token_info.bump
+    )]
+    pub token_info: Account<'info, TokenInfo>,
+
+    #[account(
+        mut,
+        seeds = [b"rules", token_info.token_mint.as_ref()],
+        bump = rule_set.bump
+    )]
+    pub rule_set: Account<'info, RuleSet>,
+
+    /// CHECK: ExtraAccountMetaList Account, must use these seeds
+    #[account(
+        mut,
+        seeds = [b"extra-account-metas", token_info.token_mint.as_ref()],
+        bump
+    )]
+    pub extra_account_meta_list: UncheckedAccount<'info>,
+
+    /// Must hold the role each setting requires; see `Role`. Pays for a
+    /// larger rule set and meta list.
+    #[account(mut)]
+    pub authority: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+impl<'info> UpdateRuleSettings<'info> {
+    /// Adds a `Whitelist` rule on the source side, or removes every
+    /// `Whitelist` rule. Disabling is immediate; enabling goes through the
+    /// change queue while the timelock is on.
+    pub fn update_whitelist(&mut self, enable: bool) -> Result<()> {
+        self.require_role(Role::WhitelistManager)?;
+        require!(
+            !enable || !self.token_info.is_timelocked(),
+            CustomError::TimelockRequired
+        );
+
+        toggle_rule(&mut self.rule_set, ToggledRule::Whitelist, enable, self.authority.key())?;
+        self.save_rule_set()
+    }
+
+    /// Goes through the change queue while the timelock is on
+    pub fn set_whitelist_mode(&mut self, mode: WhitelistMode) -> Result<()> {
+        self.require_role(Role::WhitelistManager)?;
+        require!(!self.token_info.is_timelocked(), CustomError::TimelockRequired);
+
+        change_whitelist_mode(&mut self.token_info, mode, self.authority.key());
+        self.save_rule_set()
+    }
+
+    /// Changes the side the `Whitelist` rule checks. Goes through the change
+    /// queue while the timelock is on.
+    pub fn set_whitelist_direction(&mut self, direction: WhitelistDirection) -> Result<()> {
+        self.require_role(Role::WhitelistManager)?;
+        require!(!self.token_info.is_timelocked(), CustomError::TimelockRequired);
+
+        set_whitelist_direction(&mut self.rule_set, direction, self.authority.key())?;
+        self.save_rule_set()
+    }
+
+    /// Disabling is immediate; enabling goes through the change queue while
+    /// the timelock is on
+    pub fn update_denylist(&mut self, enable: bool) -> Result<()> {
+        self.require_role(Role::WhitelistManager)?;
+        require!(
+            !enable || !self.token_info.is_timelocked(),
+            CustomError::TimelockRequired
+        );
+
+        toggle_rule(&mut self.rule_set, ToggledRule::Denylist, enable, self.authority.key())?;
+        self.save_rule_set()
+    }
+
+    /// Caps how much each wallet may send in any rolling `window_seconds`
+    /// period, tracked in its `WalletState`. Disabling is immediate; enabling
+    /// goes through the change queue while the timelock is on.
+    pub fn set_transfer_quota(&mut self, enable: bool, quota: u64, window_seconds: i64) -> Result<()> {
+        self.require_role(Role::LimitsManager)?;
+        require!(
+            !enable || !self.token_info.is_timelocked(),
+            CustomError::TimelockRequired
+        );
+
+        set_transfer_quota(&mut self.rule_set, enable, quota, window_seconds, self.authority.key())?;
+        self.save_rule_set()
+    }
+
+    /// Requires `interval` seconds or slots between two outbound transfers of
+    /// the same wallet, tracked in its `WalletState`. Disabling is immediate;
+    /// enabling goes through the change queue while the timelock is on.
+    pub fn set_transfer_cooldown(&mut self, enable: bool, interval: u64, unit: CooldownUnit) -> Result<()> {
+        self.require_role(Role::LimitsManager)?;
+        require!(
+            !enable || !self.token_info.is_timelocked(),
+            CustomError::TimelockRequired
+        );
+
+        set_transfer_cooldown(&mut self.rule_set, enable, interval, unit, self.authority.key())?;
+        self.save_rule_set()
+    }
+
+    /// Disabling is immediate; enabling goes through the change queue while
+    /// the timelock is on
+    pub fn update_lockups(&mut self, enable: bool) -> Result<()> {
+        self.require_role(Role::LimitsManager)?;
+        require!(
+            !enable || !self.token_info.is_timelocked(),
+            CustomError::TimelockRequired
+        );
+
+        toggle_rule(&mut self.rule_set, ToggledRule::Lockups, enable, self.authority.key())?;
+        self.save_rule_set()
+    }
+
+    /// `limit` is read in the mode of the current `MaxTransferAmount` rule,
+    /// `Absolute` (base units) when there is none. Replaces a decaying limit.
+    pub fn set_max_transfer_limit(&mut self, limit: u64) -> Result<()> {
+        self.require_role(Role::LimitsManager)?;
+        require!(!self.token_info.is_timelocked(), CustomError::TimelockRequired);
+
+        let mode = match self.rule_set.find(Rule::is_transfer_limit) {
+            Some(&Rule::MaxTransferAmount { mode, .. }) => mode,
+            _ => ThresholdMode::Absolute,
+        };
+        change_transfer_limit(
+            &mut self.rule_set,
+            Rule::MaxTransferAmount { limit, mode },
+            self.authority.key(),
+        )?;
+        self.save_rule_set()
+    }
+
+    /// Switches the `MaxTransferAmount` limit between base units and basis
+    /// points of the live mint supply. The current limit is reinterpreted,
+    /// so it must be valid in the new mode.
+    pub fn set_transfer_limit_mode(&mut self, mode: ThresholdMode) -> Result<()> {
+        self.require_role(Role::LimitsManager)?;
+        require!(!self.token_info.is_timelocked(), CustomError::TimelockRequired);
+
+        let Some(&Rule::MaxTransferAmount { limit, .. }) = self.rule_set.find(Rule::is_transfer_limit) else {
+            return err!(CustomError::RuleNotConfigured);
+        };
+        change_transfer_limit(
+            &mut self.rule_set,
+            Rule::MaxTransferAmount { limit, mode },
+            self.authority.key(),
+        )?;
+        self.save_rule_set()
+    }
+
+    /// Schedule variant of `set_max_transfer_limit`: the limit loosens on its
+    /// own and lifts entirely once the schedule ends
+    pub fn set_max_transfer_limit_schedule(&mut self, schedule: TransferLimitSchedule) -> Result<()> {
+        self.require_role(Role::LimitsManager)?;
+        require!(!self.token_info.is_timelocked(), CustomError::TimelockRequired);
+
+        change_transfer_limit(
+            &mut self.rule_set,
+            Rule::DecayingTransferLimit { schedule },
+            self.authority.key(),
+        )?;
+        self.save_rule_set()
+    }
+
+    /// Caps the balance any non-exempt destination may reach, either in base
+    /// units or in basis points of the live mint supply. The cap applies to
+    /// each destination token account on its own, so a holder can exceed it
+    /// by spreading tokens over several token accounts. Disabling is
+    /// immediate; enabling goes through the change queue while the timelock
+    /// is on.
+    pub fn set_max_wallet_balance(&mut self, enable: bool, amount: u64, mode: ThresholdMode) -> Result<()> {
+        self.require_role(Role::LimitsManager)?;
+        require!(
+            !enable || !self.token_info.is_timelocked(),
+            CustomError::TimelockRequired
+        );
+
+        set_max_wallet_balance(&mut self.rule_set, enable, amount, mode, self.authority.key())?;
+        self.save_rule_set()
+    }
+
+    /// Single threshold, read in the mode of the current `WhaleAlert` rule
+    /// (`Absolute` when there is none). Replaces any tiers set with
+    /// `set_whale_tiers`.
+    pub fn update_whale_alert(&mut self, enable: bool, amount: u64) -> Result<()> {
+        self.require_role(Role::LimitsManager)?;
+
+        let mode = match self.rule_set.find(is_whale_alert) {
+            Some(&Rule::WhaleAlert { mode, .. }) => mode,
+            _ => ThresholdMode::Absolute,
+        };
+        let rule = enable.then(|| Rule::WhaleAlert { thresholds: vec![amount], mode });
+        change_whale_alert(&mut self.rule_set, rule, self.authority.key())?;
+        self.save_rule_set()
+    }
+
+    /// Switches the whale thresholds between base units and basis points of
+    /// the live mint supply. The current tiers are reinterpreted, so they
+    /// must be valid in the new mode.
+    pub fn set_whale_alert_mode(&mut self, mode: ThresholdMode) -> Result<()> {
+        self.require_role(Role::LimitsManager)?;
+
+        let Some(Rule::WhaleAlert { thresholds, .. }) = self.rule_set.find(is_whale_alert).cloned() else {
+            return err!(CustomError::RuleNotConfigured);
+        };
+        change_whale_alert(
+            &mut self.rule_set,
+            Some(Rule::WhaleAlert { thresholds, mode }),
+            self.authority.key(),
+        )?;
+        self.save_rule_set()
+    }
+
+    /// `thresholds` are the ascending tiers (e.g. large, whale, mega-whale),
+    /// read in the mode of the current `WhaleAlert` rule; each alert is
+    /// tagged with the highest one the transfer reaches
+    pub fn set_whale_tiers(&mut self, thresholds: Vec<u64>) -> Result<()> {
+        self.require_role(Role::LimitsManager)?;
+
+        let Some(&Rule::WhaleAlert { mode, .. }) = self.rule_set.find(is_whale_alert) else {
+            return err!(CustomError::RuleNotConfigured);
+        };
+        change_whale_alert(
+            &mut self.rule_set,
+            Some(Rule::WhaleAlert { thresholds, mode }),
+            self.authority.key(),
+        )?;
+        self.save_rule_set()
+    }
+
+    /// Fits the rule set to its rules and the meta list to the accounts they read
+    fn save_rule_set(&self) -> Result<()> {
+        resize_rule_set(
+            &self.rule_set,
+            &self.authority.to_account_info(),
+            &self.system_program.to_account_info(),
+        )?;
+        rewrite_extra_account_meta_list(
+            &self.extra_account_meta_list.to_account_info(),
+            &self.token_info,
+            &self.rule_set,
+            &self.authority.to_account_info(),
+            &self.system_program.to_account_info(),
+        )
+    }
+
+    fn require_role(&self, role: Role) -> Result<()> {
+        self.token_info.require_role(role, &self.authority.key())
+    }
+}
+
+// The changes below are shared by the setters above and `execute_change`,
+// so a queued change has the same effect and emits the same event.
+
+/// Adds the rule behind `toggled` unless it is already there, or removes it
+pub(crate) fn toggle_rule(
+    rule_set: &mut RuleSet,
+    toggled: ToggledRule,
+    enable: bool,
+    authority: Pubkey,
+) -> Result<()> {
+    let matches = |rule: &Rule| match toggled {
+        ToggledRule::Whitelist => matches!(rule, Rule::Whitelist { .. }),
+        ToggledRule::Denylist => *rule == Rule::Denylist,
+        ToggledRule::Lockups => *rule == Rule::Lockups,
+    };
+    let old_enabled = rule_set.find(matches).is_some();
+
+    if !enable {
+        rule_set.remove_where(matches);
+    } else if !old_enabled {
+        let rule = match toggled {
+            ToggledRule::Whitelist => Rule::Whitelist {
+                direction: WhitelistDirection::Source,
+            },
+            ToggledRule::Denylist => Rule::Denylist,
+            ToggledRule::Lockups => Rule::Lockups,
+        };
+        rule_set.put(rule, matches)?;
+    }
+
+    emit!(RuleToggledEvent {
+        token_mint: rule_set.token_mint,
+        rule: toggled,
+        old_enabled,
+        new_enabled: enable,
+        authority,
+    });
+    Ok(())
+}
+
+pub(crate) fn change_whitelist_mode(token_info: &mut TokenInfo, mode: WhitelistMode, authority: Pubkey) {
+    emit!(WhitelistModeChangedEvent {
+        token_mint: token_info.token_mint,
+        old_mode: token_info.whitelist_mode,
+        new_mode: mode,
+        authority,
+    });
+    token_info.whitelist_mode = mode;
+}
+
+pub(crate) fn set_whitelist_direction(
+    rule_set: &mut RuleSet,
+    direction: WhitelistDirection,
+    authority: Pubkey,
+) -> Result<()> {
+    let is_whitelist = |rule: &Rule| matches!(rule, Rule::Whitelist { .. });
+    let Some(&Rule::Whitelist { direction: old_direction }) = rule_set.find(is_whitelist) else {
+        return err!(CustomError::RuleNotConfigured);
+    };
+    rule_set.put(Rule::Whitelist { direction }, is_whitelist)?;
+
+    emit!(WhitelistDirectionChangedEvent {
+        token_mint: rule_set.token_mint,
+        old_direction,
+        new_direction: direction,
+        authority,
+    });
+    Ok(())
+}
+
+pub(crate) fn set_transfer_quota(
+    rule_set: &mut RuleSet,
+    enable: bool,
+    quota: u64,
+    window: i64,
+    authority: Pubkey,
+) -> Result<()> {
+    let is_quota = |rule: &Rule| matches!(rule, Rule::TransferQuota { .. });
+    let (old_enabled, old_quota, old_window) = match rule_set.find(is_quota) {
+        Some(&Rule::TransferQuota { quota, window }) => (true, quota, window),
+        _ => (false, 0, 0),
+    };
+    put_or_remove(rule_set, enable.then_some(Rule::TransferQuota { quota, window }), is_quota)?;
+
+    emit!(TransferQuotaChangedEvent {
+        token_mint: rule_set.token_mint,
+        old_enabled,
+        new_enabled: enable,
+        old_quota,
+        new_quota: quota,
+        old_window,
+        new_window: window,
+        authority,
+    });
+    Ok(())
+}
+
+pub(crate) fn set_transfer_cooldown(
+    rule_set: &mut RuleSet,
+    enable: bool,
+    interval: u64,
+    unit: CooldownUnit,
+    authority: Pubkey,
+) -> Result<()> {
+    let is_cooldown = |rule: &Rule| matches!(rule, Rule::Cooldown { .. });
+    let (old_enabled, old_interval, old_unit) = match rule_set.find(is_cooldown) {
+        Some(&Rule::Cooldown { interval, unit }) => (true, interval, unit),
+        _ => (false, 0, CooldownUnit::Seconds),
+    };
+    put_or_remove(rule_set, enable.then_some(Rule::Cooldown { interval, unit }), is_cooldown)?;
+
+    emit!(CooldownChangedEvent {
+        token_mint: rule_set.token_mint,
+        old_enabled,
+        new_enabled: enable,
+        old_interval,
+        new_interval: interval,
+        old_unit,
+        new_unit: unit,
+        authority,
+    });
+    Ok(())
+}
+
+pub(crate) fn set_max_wallet_balance(
+    rule_set: &mut RuleSet,
+    enable: bool,
+    amount: u64,
+    mode: ThresholdMode,
+    authority: Pubkey,
+) -> Result<()> {
+    let is_balance_cap = |rule: &Rule| matches!(rule, Rule::MaxWalletBalance { .. });
+    let (old_enabled, old_amount, old_mode) = match rule_set.find(is_balance_cap) {
+        Some(&Rule::MaxWalletBalance { limit, mode }) => (true, limit, mode),
+        _ => (false, 0, ThresholdMode::Absolute),
+    };
+    let rule = Rule::MaxWalletBalance { limit: amount, mode };
+    put_or_remove(rule_set, enable.then_some(rule), is_balance_cap)?;
+
+    emit!(MaxWalletBalanceChangedEvent {
+        token_mint: rule_set.token_mint,
+        old_enabled,
+        new_enabled: enable,
+        old_amount,
+        new_amount: amount,
+        old_mode,
+        new_mode: mode,
+        authority,
+    });
+    Ok(())
+}
+
+/// Replaces the current per-transfer limit, fixed or decaying, with `rule`
+pub(crate) fn change_transfer_limit(rule_set: &mut RuleSet, rule: Rule, authority: Pubkey) -> Result<()> {
+    let (old_enabled, old_limit, old_mode, old_schedule) = transfer_limit_settings(rule_set);
+    put_or_remove(rule_set, Some(rule), Rule::is_transfer_limit)?;
+    let (new_enabled, new_limit, new_mode, new_schedule) = transfer_limit_settings(rule_set);
+
+    emit!(TransferLimitChangedEvent {
+        token_mint: rule_set.token_mint,
+        old_enabled,
+        new_enabled,
+        old_limit,
+        new_limit,
+        old_mode,
+        new_mode,
+        old_schedule,
+        new_schedule,
+        authority,
+    });
+    Ok(())
+}
+
+/// Replaces the `WhaleAlert` rule with `rule`, or removes it for `None`
+pub(crate) fn change_whale_alert(rule_set: &mut RuleSet, rule: Option<Rule>, authority: Pubkey) -> Result<()> {
+    let (old_enabled, old_thresholds, old_mode) = whale_alert_settings(rule_set);
+    put_or_remove(rule_set, rule, is_whale_alert)?;
+    let (new_enabled, new_thresholds, new_mode) = whale_alert_settings(rule_set);
+
+    emit!(WhaleAlertChangedEvent {
+        token_mint: rule_set.token_mint,
+        old_enabled,
+        new_enabled,
+        old_thresholds,
+        new_thresholds,
+        old_mode,
+        new_mode,
+        authority,
+    });
+    Ok(())
+}
+
+/// Puts a validated `rule` in place of the rules `replaces` picks, or removes
+/// them for `None`
+fn put_or_remove(rule_set: &mut RuleSet, rule: Option<Rule>, replaces: impl Fn(&Rule) -> bool) -> Result<()> {
+    match rule {
+        Some(rule) => {
+            rule.validate()?;
+            rule_set.put(rule, replaces)
+        }
+        None => {
+            rule_set.remove_where(replaces);
+            Ok(())
+        }
+    }
+}
+
+fn is_whale_alert(rule: &Rule) -> bool {
+    matches!(rule, Rule::WhaleAlert { .. })
+}
+
+/// The per-transfer limit as `TransferLimitChangedEvent` reports it
+fn transfer_limit_settings(
+    rule_set: &RuleSet,
+) -> (bool, u64, ThresholdMode, Option<TransferLimitSchedule>) {
+    match rule_set.find(Rule::is_transfer_limit) {
+        Some(&Rule::MaxTransferAmount { limit, mode }) => (true, limit, mode, None),
+        Some(&Rule::DecayingTransferLimit { schedule }) => {
+            (true, 0, ThresholdMode::Absolute, Some(schedule))
+        }
+        _ => (false, 0, ThresholdMode::Absolute, None),
+    }
+}
+
+/// The whale alert as `WhaleAlertChangedEvent` reports it
+fn whale_alert_settings(rule_set: &RuleSet) -> (bool, Vec<u64>, ThresholdMode) {
+    match rule_set.find(is_whale_alert) {
+        Some(Rule::WhaleAlert { thresholds, mode }) => (true, thresholds.clone(), *mode),
+        _ => (false, vec![], ThresholdMode::Absolute),
+    }
+}
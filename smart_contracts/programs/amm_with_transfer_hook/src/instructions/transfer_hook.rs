@@ -13,16 +13,21 @@ use anchor_spl::{
 };
 
 use crate::{
-    state::TokenInfo,
+    state::{
+        Lockup, RuleAccounts, RuleSet, TokenInfo, TransferFacts, TransferStats, WalletState,
+        WhitelistMode,
+    },
     errors::CustomError,
     events::WhaleTransferEvent,
 };
 
+/// Accounts follow the SPL transfer-hook `Execute` layout: source, mint,
+/// destination, owner and the validation account come first, followed by the
+/// extra accounts resolved from the `ExtraAccountMetaList`.
 #[derive(Accounts)]
 pub struct TransferHook<'info> {
     #[account(
-        token::mint = mint, 
-        token::authority = owner,
+        token::mint = mint,
     )]
     pub source_token: InterfaceAccount<'info, TokenAccount>,
 
@@ -33,52 +38,155 @@ pub struct TransferHook<'info> {
     )]
     pub destination_token: InterfaceAccount<'info, TokenAccount>,
 
-    /// CHECK: Owner can be a SystemAccount or PDA
+    /// CHECK: Transfer authority, which may be the owner or a delegate. Per-wallet
+    /// rules are keyed on `source_token.owner` so a delegate cannot sidestep them.
     pub owner: UncheckedAccount<'info>,
 
-    /// Token info holds whitelist, whale settings, and transfer limits
+    /// CHECK: ExtraAccountMetaList Account
+    #[account(
+        seeds = [b"extra-account-metas", mint.key().as_ref()], 
+        bump
+    )]
+    pub extra_account_meta_list: UncheckedAccount<'info>,
+
+    /// Token info holds the pause switch, launch schedule and whitelist
     #[account(
         seeds = [b"token-info", mint.key().as_ref()],
         bump = token_info.bump,
     )]
     pub token_info: Account<'info, TokenInfo>,
 
-    /// CHECK: ExtraAccountMetaList Account
+    /// Every other check, in the order the hook runs them
     #[account(
-        seeds = [b"extra-account-metas", mint.key().as_ref()], 
-        bump
+        seeds = [b"rules", mint.key().as_ref()],
+        bump = rule_set.bump,
     )]
-    pub extra_account_meta_list: UncheckedAccount<'info>,
+    pub rule_set: Account<'info, RuleSet>,
 }
 
 impl<'info> TransferHook<'info> {
-    /// Called during token transfer
-    pub fn transfer_hook(&mut self, amount: u64) -> Result<()> {
+    /// Called during token transfer. `rule_accounts` are the extra accounts
+    /// resolved after `rule_set` for the rules enabled on the mint.
+    pub fn transfer_hook(&mut self, amount: u64, rule_accounts: &[AccountInfo]) -> Result<()> {
         self.check_is_transferring()?;
 
         let ti = &self.token_info;
 
-        // 1) Enforce whitelist
-        if ti.is_whitelist_enabled && !ti.whitelist_addresses.contains(&self.owner.key()) {
-            return err!(CustomError::NotWhitelisted);
+        // 1) Halt everything but recovery wallets while paused
+        if ti.is_paused
+            && !ti.pause_exemptions.contains(&self.source_token.owner)
+            && !ti.pause_exemptions.contains(&self.destination_token.owner)
+        {
+            return err!(CustomError::TransfersPaused);
         }
 
-        // 2) Enforce max single transfer limit
-        if ti.is_total_transfer_amount_enabled && amount > ti.total_transfer_amount {
-            return err!(CustomError::TransferLimitExceeded);
+        // 2) Enforce trading-open time and launch phase limits
+        if !ti.launch_exemptions.contains(&self.source_token.owner) {
+            self.check_launch_schedule(amount)?;
         }
 
-        // 3) Whale detection event
-        if ti.is_whale_enabled && amount >= ti.whale_amount {
-            emit!(WhaleTransferEvent {
-                whale_address: self.owner.key(),
-                transfer_amount: amount,
-            });
+        // 3) Evaluate the mint's ordered rules. Token-2022 moves the tokens
+        // before invoking the hook, so both balances already include `amount`.
+        let clock = Clock::get()?;
+        let mut wallet_state = if self.rule_set.uses_wallet_state() {
+            Some(load_rule_state::<WalletState>(self.wallet_state_account(rule_accounts)?)?)
+        } else {
+            None
+        };
+        let tier = self.rule_set.evaluate(
+            &self.transfer_facts(amount, &clock),
+            &HookRuleAccounts { hook: self, rule_accounts },
+            wallet_state.as_mut(),
+        )?;
+
+        // 4) Record the transfer in the owner's WalletState for the next cooldown
+        // and quota checks
+        if let Some(mut state) = wallet_state {
+            state.last_transfer_at = clock.unix_timestamp;
+            state.last_transfer_slot = clock.slot;
+            store_rule_state(self.wallet_state_account(rule_accounts)?, &state)?;
+        }
+
+        // 5) At most one whale event per transfer, tagged with the highest tier
+        // any `WhaleAlert` rule reports
+        let whale_alert = tier > 0;
+        if whale_alert {
+            emit!(WhaleTransferEvent::new(&self.transfer_facts(amount, &clock), tier));
+        }
+
+        // 6) Record the accepted transfer in the mint's statistics
+        if ti.has_transfer_stats {
+            self.record_transfer_stats(amount, whale_alert, rule_accounts)?;
+        }
+
+        Ok(())
+    }
+
+    fn transfer_facts<'a>(&self, amount: u64, clock: &'a Clock) -> TransferFacts<'a> {
+        let ti = &self.token_info;
+        TransferFacts {
+            mint: self.mint.key(),
+            source_token: self.source_token.key(),
+            owner: self.source_token.owner,
+            destination_token: self.destination_token.key(),
+            destination_owner: self.destination_token.owner,
+            amount,
+            source_balance: self.source_token.amount,
+            destination_balance: self.destination_token.amount,
+            balance_cap_exempt: ti
+                .is_balance_cap_exempt(&self.destination_token.key(), &self.destination_token.owner),
+            supply: self.mint.supply,
+            clock,
+        }
+    }
+
+    fn record_transfer_stats(&self, amount: u64, whale_alert: bool, rule_accounts: &[AccountInfo]) -> Result<()> {
+        let account = find_rule_account(rule_accounts, &[b"stats", self.mint.key().as_ref()])?;
+        require!(is_program_account(account), CustomError::MissingRuleAccount);
+
+        let mut stats = load_rule_state::<TransferStats>(account)?;
+        stats.record(amount, whale_alert);
+        store_rule_state(account, &stats)
+    }
+
+    fn check_launch_schedule(&self, amount: u64) -> Result<()> {
+        let ti = &self.token_info;
+        let now = Clock::get()?.unix_timestamp;
+
+        require!(now >= ti.trading_opens_at, CustomError::TradingNotOpen);
+
+        if ti.is_in_launch_phase(now) {
+            if ti.launch_max_transfer_amount > 0 {
+                require!(
+                    amount <= ti.launch_max_transfer_amount,
+                    CustomError::LaunchTransferLimitExceeded
+                );
+            }
+            if ti.launch_max_wallet_balance > 0
+                && !ti.is_balance_cap_exempt(&self.destination_token.key(), &self.destination_token.owner)
+            {
+                require!(
+                    self.destination_token.amount <= ti.launch_max_wallet_balance,
+                    CustomError::LaunchWalletBalanceExceeded
+                );
+            }
         }
 
         Ok(())
     }
 
+    fn wallet_state_account<'a, 'b>(
+        &self,
+        rule_accounts: &'a [AccountInfo<'b>],
+    ) -> Result<&'a AccountInfo<'b>> {
+        let account = find_rule_account(
+            rule_accounts,
+            &[b"wallet-state", self.mint.key().as_ref(), self.source_token.owner.as_ref()],
+        )?;
+        require!(is_program_account(account), CustomError::WalletStateNotInitialized);
+        Ok(account)
+    }
+
     fn check_is_transferring(&mut self) -> Result<()> {
         let source_token_info = self.source_token.to_account_info();
         let mut data_ref: RefMut<&mut [u8]> = source_token_info.try_borrow_mut_data()?;
@@ -91,4 +199,72 @@ impl<'info> TransferHook<'info> {
 
         Ok(())
     }
-}
\ No newline at end of file
+}
+
+/// The per-wallet accounts of the transfer, as the rules see them
+struct HookRuleAccounts<'a, 'info, 'b> {
+    hook: &'a TransferHook<'info>,
+    rule_accounts: &'a [AccountInfo<'b>],
+}
+
+impl HookRuleAccounts<'_, '_, '_> {
+    fn has_entry(&self, seed: &[u8], wallet: &Pubkey) -> Result<bool> {
+        let entry = find_rule_account(
+            self.rule_accounts,
+            &[seed, self.hook.mint.key().as_ref(), wallet.as_ref()],
+        )?;
+        Ok(is_program_account(entry))
+    }
+}
+
+impl RuleAccounts for HookRuleAccounts<'_, '_, '_> {
+    fn is_whitelisted(&self, wallet: &Pubkey) -> Result<bool> {
+        match self.hook.token_info.whitelist_mode {
+            WhitelistMode::List => Ok(self.hook.token_info.whitelist_addresses.contains(wallet)),
+            WhitelistMode::PerWallet | WhitelistMode::Merkle => self.has_entry(b"whitelist", wallet),
+        }
+    }
+
+    fn is_denylisted(&self, wallet: &Pubkey) -> Result<bool> {
+        self.has_entry(b"denylist", wallet)
+    }
+
+    fn locked_amount(&self, now: i64) -> Result<u64> {
+        let account = find_rule_account(
+            self.rule_accounts,
+            &[b"lockup", self.hook.mint.key().as_ref(), self.hook.source_token.owner.as_ref()],
+        )?;
+        if !is_program_account(account) {
+            return Ok(0);
+        }
+        Ok(load_rule_state::<Lockup>(account)?.locked_amount(now))
+    }
+}
+
+/// Finds the PDA derived from `seeds` among the extra accounts passed to the hook
+fn find_rule_account<'a, 'info>(
+    rule_accounts: &'a [AccountInfo<'info>],
+    seeds: &[&[u8]],
+) -> Result<&'a AccountInfo<'info>> {
+    let (address, _) = Pubkey::find_program_address(seeds, &crate::ID);
+    rule_accounts
+        .iter()
+        .find(|account| account.key() == address)
+        .ok_or_else(|| error!(CustomError::MissingRuleAccount))
+}
+
+/// A rule PDA that was never created (or was closed) is still passed to the
+/// hook, but is not owned by this program
+fn is_program_account(account: &AccountInfo) -> bool {
+    account.owner == &crate::ID && !account.data_is_empty()
+}
+
+fn load_rule_state<T: AccountDeserialize>(account: &AccountInfo) -> Result<T> {
+    T::try_deserialize(&mut &account.try_borrow_data()?[..])
+}
+
+fn store_rule_state<T: AccountSerialize>(account: &AccountInfo, state: &T) -> Result<()> {
+    let mut data = account.try_borrow_mut_data()?;
+    let mut writer: &mut [u8] = &mut data;
+    state.try_serialize(&mut writer)
+}
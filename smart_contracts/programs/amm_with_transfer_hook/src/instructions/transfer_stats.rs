@@ -0,0 +1,136 @@
+use anchor_lang::prelude::*;
+
+use crate::{
+    instructions::rewrite_extra_account_meta_list,
+    state::{CustomError, RuleSet, TokenInfo, TransferStats, TransferStatsToggledEvent},
+};
+
+#[derive(Accounts)]
+pub struct InitializeTransferStats<'info> {
+    #[account(
+        mut,
+        seeds = [b"token-info", token_info.token_mint.as_ref()],
+        bump = token_info.bump,
+        constraint = token_info.can_update_meta_list(&authority.key()) @ CustomError::MissingRole,
+    )]
+    pub token_info: Account<'info, TokenInfo>,
+
+    #[account(
+        init,
+        seeds = [b"stats", token_info.token_mint.as_ref()],
+        bump,
+        space = TransferStats::INIT_SPACE,
+        payer = authority
+    )]
+    pub transfer_stats: Account<'info, TransferStats>,
+
+    /// CHECK: ExtraAccountMetaList Account, must use these seeds
+    #[account(
+        mut,
+        seeds = [b"extra-account-metas", token_info.token_mint.as_ref()],
+        bump
+    )]
+    pub extra_account_meta_list: UncheckedAccount<'info>,
+
+    #[account(
+        seeds = [b"rules", token_info.token_mint.as_ref()],
+        bump = rule_set.bump
+    )]
+    pub rule_set: Account<'info, RuleSet>,
+
+    #[account(mut)]
+    pub authority: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+impl<'info> InitializeTransferStats<'info> {
+    /// Adds the stats account to the meta list as writable, so every
+    /// transfer of the mint write-locks it from here on
+    pub fn initialize_transfer_stats(&mut self, bumps: InitializeTransferStatsBumps) -> Result<()> {
+        self.transfer_stats.set_inner(TransferStats {
+            token_mint: self.token_info.token_mint,
+            bump: bumps.transfer_stats,
+            transfer_count: 0,
+            total_volume: 0,
+            largest_transfer: 0,
+            whale_alert_count: 0,
+        });
+        self.token_info.has_transfer_stats = true;
+
+        emit!(TransferStatsToggledEvent {
+            token_mint: self.token_info.token_mint,
+            old_enabled: false,
+            new_enabled: true,
+            authority: self.authority.key(),
+        });
+
+        rewrite_extra_account_meta_list(
+            &self.extra_account_meta_list.to_account_info(),
+            &self.token_info,
+            &self.rule_set,
+            &self.authority.to_account_info(),
+            &self.system_program.to_account_info(),
+        )
+    }
+}
+
+/// Stops collecting statistics and drops the stats account from the meta list
+#[derive(Accounts)]
+pub struct CloseTransferStats<'info> {
+    #[account(
+        mut,
+        seeds = [b"token-info", token_info.token_mint.as_ref()],
+        bump = token_info.bump,
+        constraint = token_info.can_update_meta_list(&authority.key()) @ CustomError::MissingRole,
+    )]
+    pub token_info: Account<'info, TokenInfo>,
+
+    #[account(
+        mut,
+        close = authority,
+        seeds = [b"stats", token_info.token_mint.as_ref()],
+        bump = transfer_stats.bump
+    )]
+    pub transfer_stats: Account<'info, TransferStats>,
+
+    /// CHECK: ExtraAccountMetaList Account, must use these seeds
+    #[account(
+        mut,
+        seeds = [b"extra-account-metas", token_info.token_mint.as_ref()],
+        bump
+    )]
+    pub extra_account_meta_list: UncheckedAccount<'info>,
+
+    #[account(
+        seeds = [b"rules", token_info.token_mint.as_ref()],
+        bump = rule_set.bump
+    )]
+    pub rule_set: Account<'info, RuleSet>,
+
+    #[account(mut)]
+    pub authority: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+impl<'info> CloseTransferStats<'info> {
+    pub fn close_transfer_stats(&mut self) -> Result<()> {
+        self.token_info.has_transfer_stats = false;
+
+        emit!(TransferStatsToggledEvent {
+            token_mint: self.token_info.token_mint,
+            old_enabled: true,
+            new_enabled: false,
+            authority: self.authority.key(),
+        });
+
+        rewrite_extra_account_meta_list(
+            &self.extra_account_meta_list.to_account_info(),
+            &self.token_info,
+            &self.rule_set,
+            &self.authority.to_account_info(),
+            &self.system_program.to_account_info(),
+        )
+    }
+}
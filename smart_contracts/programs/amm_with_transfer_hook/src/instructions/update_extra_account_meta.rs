@@ -0,0 +1,90 @@
+use anchor_lang::prelude::*;
+use anchor_spl::token_interface::Mint;
+use spl_tlv_account_resolution::state::ExtraAccountMetaList;
+use spl_transfer_hook_interface::instruction::ExecuteInstruction;
+
+use crate::{
+    instructions::InitializeExtraAccountMetaList,
+    state::{CustomError, RuleSet, TokenInfo},
+    util::resize_account,
+};
+
+#[derive(Accounts)]
+pub struct UpdateExtraAccountMetaList<'info> {
+    /// Admin, whitelist manager or limits manager: any key whose settings
+    /// can change the rule accounts
+    #[account(mut)]
+    pub authority: Signer<'info>,
+
+    /// CHECK: ExtraAccountMetaList Account, must use these seeds
+    #[account(
+        mut,
+        seeds = [b"extra-account-metas", mint.key().as_ref()],
+        bump
+    )]
+    pub extra_account_meta_list: UncheckedAccount<'info>,
+
+    #[account(
+        constraint = token_info.can_update_meta_list(&authority.key()) @ CustomError::MissingRole,
+        seeds = [b"token-info", mint.key().as_ref()],
+        bump = token_info.bump
+    )]
+    pub token_info: Account<'info, TokenInfo>,
+
+    #[account(
+        seeds = [b"rules", mint.key().as_ref()],
+        bump = rule_set.bump
+    )]
+    pub rule_set: Account<'info, RuleSet>,
+
+    pub mint: InterfaceAccount<'info, Mint>,
+    pub system_program: Program<'info, System>,
+}
+
+impl<'info> UpdateExtraAccountMetaList<'info> {
+    /// Rewrites the meta list so it matches the accounts the current rules
+    /// need. Settings that change those accounts already do this, so this is
+    /// only needed to repair a list written by an older program version.
+    pub fn update_extra_account_meta_list(&mut self) -> Result<()> {
+        rewrite_extra_account_meta_list(
+            &self.extra_account_meta_list.to_account_info(),
+            &self.token_info,
+            &self.rule_set,
+            &self.authority.to_account_info(),
+            &self.system_program.to_account_info(),
+        )
+    }
+}
+
+/// Rewrites `meta_list` from `token_info` and `rule_set`, growing or
+/// shrinking the account to fit and settling the rent difference with
+/// `payer`. Called by every instruction that changes the rule accounts.
+pub fn rewrite_extra_account_meta_list<'info>(
+    meta_list: &AccountInfo<'info>,
+    token_info: &TokenInfo,
+    rule_set: &RuleSet,
+    payer: &AccountInfo<'info>,
+    system_program: &AccountInfo<'info>,
+) -> Result<()> {
+    let metas = InitializeExtraAccountMetaList::extra_account_metas(token_info, rule_set)?;
+    let new_size = ExtraAccountMetaList::size_of(metas.len())?;
+    let current_size = meta_list.data_len();
+
+    // The TLV entry is rewritten in place, so grow before and shrink after
+    if new_size > current_size {
+        resize_account(meta_list, payer, system_program, new_size)?;
+    }
+
+    // The first program version created the account without writing the list
+    if meta_list.try_borrow_data()?.iter().all(|&byte| byte == 0) {
+        ExtraAccountMetaList::init::<ExecuteInstruction>(&mut meta_list.try_borrow_mut_data()?, &metas)?;
+    } else {
+        ExtraAccountMetaList::update::<ExecuteInstruction>(&mut meta_list.try_borrow_mut_data()?, &metas)?;
+    }
+
+    if new_size < current_size {
+        resize_account(meta_list, payer, system_program, new_size)?;
+    }
+
+    Ok(())
+}
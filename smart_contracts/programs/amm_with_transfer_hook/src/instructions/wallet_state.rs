@@ -0,0 +1,47 @@
+use anchor_lang::prelude::*;
+
+use crate::state::{TokenInfo, WalletState, QUOTA_BUCKETS};
+
+/// Permissionless: anyone may pay to create a wallet's state account, which
+/// the hook requires once a per-wallet rule is enabled.
+#[derive(Accounts)]
+pub struct InitializeWalletState<'info> {
+    #[account(mut)]
+    pub payer: Signer<'info>,
+
+    /// CHECK: Any wallet that may send the token
+    pub wallet: UncheckedAccount<'info>,
+
+    #[account(
+        seeds = [b"token-info", token_info.token_mint.as_ref()],
+        bump = token_info.bump
+    )]
+    pub token_info: Account<'info, TokenInfo>,
+
+    #[account(
+        init,
+        seeds = [b"wallet-state", token_info.token_mint.as_ref(), wallet.key().as_ref()],
+        bump,
+        space = WalletState::INIT_SPACE,
+        payer = payer
+    )]
+    pub wallet_state: Account<'info, WalletState>,
+
+    pub system_program: Program<'info, System>,
+}
+
+impl<'info> InitializeWalletState<'info> {
+    pub fn initialize_wallet_state(&mut self, bumps: InitializeWalletStateBumps) -> Result<()> {
+        self.wallet_state.set_inner(WalletState {
+            token_mint: self.token_info.token_mint,
+            wallet: self.wallet.key(),
+            quota_buckets: [0; QUOTA_BUCKETS],
+            quota_last_bucket: 0,
+            quota_bucket_width: 0,
+            bump: bumps.wallet_state,
+            last_transfer_at: 0,
+            last_transfer_slot: 0,
+        });
+        Ok(())
+    }
+}
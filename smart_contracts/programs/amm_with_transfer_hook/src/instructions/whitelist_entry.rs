@@ -0,0 +1,144 @@
+use anchor_lang::{prelude::*, solana_program::hash::hashv};
+
+use crate::{
+    state::{CustomError, TokenInfo, WhitelistEntry, WhitelistEntryUpdatedEvent, WhitelistMode},
+    util::verify_merkle_proof,
+};
+
+#[derive(Accounts)]
+#[instruction(wallet: Pubkey)]
+pub struct AddWhitelistEntry<'info> {
+    #[account(
+        has_one = whitelist_manager,
+        seeds = [b"token-info", token_info.token_mint.as_ref()],
+        bump = token_info.bump
+    )]
+    pub token_info: Account<'info, TokenInfo>,
+
+    #[account(
+        init,
+        seeds = [b"whitelist", token_info.token_mint.as_ref(), wallet.as_ref()],
+        bump,
+        space = WhitelistEntry::INIT_SPACE,
+        payer = whitelist_manager
+    )]
+    pub whitelist_entry: Account<'info, WhitelistEntry>,
+
+    #[account(mut)]
+    pub whitelist_manager: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+impl<'info> AddWhitelistEntry<'info> {
+    pub fn add_whitelist_entry(&mut self, bumps: AddWhitelistEntryBumps, wallet: Pubkey) -> Result<()> {
+        self.whitelist_entry.set_inner(WhitelistEntry {
+            token_mint: self.token_info.token_mint,
+            wallet,
+            bump: bumps.whitelist_entry,
+        });
+
+        emit!(WhitelistEntryUpdatedEvent {
+            token_mint: self.token_info.token_mint,
+            wallet,
+            was_listed: false,
+            is_listed: true,
+            authority: self.whitelist_manager.key(),
+        });
+        Ok(())
+    }
+}
+
+#[derive(Accounts)]
+pub struct RemoveWhitelistEntry<'info> {
+    #[account(
+        has_one = whitelist_manager,
+        seeds = [b"token-info", token_info.token_mint.as_ref()],
+        bump = token_info.bump
+    )]
+    pub token_info: Account<'info, TokenInfo>,
+
+    #[account(
+        mut,
+        close = whitelist_manager,
+        seeds = [b"whitelist", token_info.token_mint.as_ref(), whitelist_entry.wallet.as_ref()],
+        bump = whitelist_entry.bump
+    )]
+    pub whitelist_entry: Account<'info, WhitelistEntry>,
+
+    #[account(mut)]
+    pub whitelist_manager: Signer<'info>,
+}
+
+impl<'info> RemoveWhitelistEntry<'info> {
+    pub fn remove_whitelist_entry(&mut self) -> Result<()> {
+        emit!(WhitelistEntryUpdatedEvent {
+            token_mint: self.token_info.token_mint,
+            wallet: self.whitelist_entry.wallet,
+            was_listed: true,
+            is_listed: false,
+            authority: self.whitelist_manager.key(),
+        });
+        Ok(())
+    }
+}
+
+#[derive(Accounts)]
+pub struct RegisterWhitelistMembership<'info> {
+    #[account(
+        seeds = [b"token-info", token_info.token_mint.as_ref()],
+        bump = token_info.bump
+    )]
+    pub token_info: Account<'info, TokenInfo>,
+
+    #[account(
+        init,
+        seeds = [b"whitelist", token_info.token_mint.as_ref(), wallet.key().as_ref()],
+        bump,
+        space = WhitelistEntry::INIT_SPACE,
+        payer = wallet
+    )]
+    pub whitelist_entry: Account<'info, WhitelistEntry>,
+
+    #[account(mut)]
+    pub wallet: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+impl<'info> RegisterWhitelistMembership<'info> {
+    /// Creates the caller's `WhitelistEntry` once `proof` shows
+    /// `sha256(wallet)` is a leaf of the current Merkle root.
+    pub fn register_whitelist_membership(
+        &mut self,
+        bumps: RegisterWhitelistMembershipBumps,
+        proof: Vec<[u8; 32]>,
+    ) -> Result<()> {
+        require!(
+            self.token_info.whitelist_mode == WhitelistMode::Merkle,
+            CustomError::NotMerkleWhitelist
+        );
+
+        let wallet = self.wallet.key();
+        let leaf = hashv(&[wallet.as_ref()]).to_bytes();
+        require!(
+            verify_merkle_proof(&proof, &self.token_info.whitelist_merkle_root, leaf),
+            CustomError::InvalidMerkleProof
+        );
+
+        self.whitelist_entry.set_inner(WhitelistEntry {
+            token_mint: self.token_info.token_mint,
+            wallet,
+            bump: bumps.whitelist_entry,
+        });
+
+        emit!(WhitelistEntryUpdatedEvent {
+            token_mint: self.token_info.token_mint,
+            wallet,
+            was_listed: false,
+            is_listed: true,
+            authority: wallet,
+        });
+        Ok(())
+    }
+}
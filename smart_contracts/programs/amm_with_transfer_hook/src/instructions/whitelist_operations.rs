@@ -1,84 +1,136 @@
-use anchor_lang::{prelude::*, system_program};
+use anchor_lang::prelude::*;
 
-use crate::state::TokenInfo;
+use crate::{
+    state::{
+        CustomError, Rule, RuleSet, TokenInfo, WhitelistBatchUpdatedEvent, WhitelistUpdatedEvent,
+        MAX_WHITELIST,
+    },
+    util::{insert_capped, remove_address, resize_token_info},
+};
 
 #[derive(Accounts)]
 pub struct TokenInfoWhitelistOps<'info> {
     #[account(
         mut,
-        has_one = token_creator,
+        has_one = whitelist_manager,
         seeds = [b"token-info", token_info.token_mint.as_ref()],
         bump = token_info.bump
     )]
     pub token_info: Account<'info, TokenInfo>,
 
-    pub token_creator: Signer<'info>,
+    #[account(
+        seeds = [b"rules", token_info.token_mint.as_ref()],
+        bump = rule_set.bump
+    )]
+    pub rule_set: Account<'info, RuleSet>,
+
+    #[account(mut)]
+    pub whitelist_manager: Signer<'info>,
 
     pub system_program: Program<'info, System>,
 }
 
 impl<'info> TokenInfoWhitelistOps<'info> {
     pub fn add_to_whitelist(&mut self, address: Pubkey) -> Result<()> {
-        if !self.token_info.whitelist_addresses.contains(&address) {
-            self.realloc_token_info(true)?;
-            self.token_info.whitelist_addresses.push(address);
+        self.require_whitelist_rule()?;
+
+        let whitelist = &mut self.token_info.whitelist_addresses;
+        if !insert_capped(whitelist, address, MAX_WHITELIST, CustomError::WhitelistFull)? {
+            self.emit_updated_event(address, true);
+            return Ok(());
         }
+
+        self.realloc_token_info()?;
+        self.emit_updated_event(address, false);
         Ok(())
     }
 
     pub fn remove_from_whitelist(&mut self, address: Pubkey) -> Result<()> {
-        if let Some(pos) = self
-            .token_info
-            .whitelist_addresses
-            .iter()
-            .position(|&x| x == address)
-        {
-            self.token_info.whitelist_addresses.remove(pos);
-            self.realloc_token_info(false)?;
+        if remove_address(&mut self.token_info.whitelist_addresses, &address) {
+            self.realloc_token_info()?;
+            self.emit_updated_event(address, true);
+        } else {
+            self.emit_updated_event(address, false);
         }
         Ok(())
     }
 
-    fn realloc_token_info(&mut self, is_adding: bool) -> Result<()> {
-        let account_info = self.token_info.to_account_info();
-        let pubkey_size = std::mem::size_of::<Pubkey>();
+    /// Adds every new address in `addresses`, skipping duplicates, with a
+    /// single realloc and rent top-up for the whole batch.
+    pub fn add_many_to_whitelist(&mut self, addresses: Vec<Pubkey>) -> Result<()> {
+        self.require_whitelist_rule()?;
 
-        // 4-byte vec length prefix is constant, so adjust only for element size
-        let new_size = if is_adding {
-            account_info.data_len() + pubkey_size
-        } else {
-            account_info.data_len().saturating_sub(pubkey_size)
-        };
-
-        // Calculate required rent
-        let rent = Rent::get()?;
-        let lamports_required = rent.minimum_balance(new_size);
-
-        if is_adding {
-            let rent_diff = lamports_required.saturating_sub(account_info.lamports());
-            if rent_diff > 0 {
-                // Top up account lamports
-                let cpi_accounts = system_program::Transfer {
-                    from: self.token_creator.to_account_info(),
-                    to: account_info.clone(),
-                };
-                let cpi_ctx =
-                    CpiContext::new(self.system_program.to_account_info(), cpi_accounts);
-                system_program::transfer(cpi_ctx, rent_diff)?;
+        let whitelist = &mut self.token_info.whitelist_addresses;
+        let mut added: u32 = 0;
+        for address in addresses {
+            if !whitelist.contains(&address) {
+                whitelist.push(address);
+                added += 1;
             }
-            account_info.realloc(new_size, false)?;
-            msg!("TokenInfo expanded to {} bytes", new_size);
-        } else {
-            // Shrinking: refund rent
-            account_info.realloc(new_size, false)?;
-            let refund = account_info.lamports() - lamports_required;
-            if refund > 0 {
-                **self.token_creator.to_account_info().try_borrow_mut_lamports()? += refund;
-                **account_info.try_borrow_mut_lamports()? -= refund;
-            }
-            msg!("TokenInfo shrunk to {} bytes", new_size);
         }
 
+        require!(whitelist.len() <= MAX_WHITELIST, CustomError::WhitelistFull);
+
+        if added > 0 {
+            self.realloc_token_info()?;
+        }
+
+        self.emit_batch_event(added, 0);
+        Ok(())
+    }
+
+    /// Removes every listed address that is present, with a single realloc
+    /// and rent refund for the whole batch.
+    pub fn remove_many_from_whitelist(&mut self, addresses: Vec<Pubkey>) -> Result<()> {
+        let whitelist = &mut self.token_info.whitelist_addresses;
+        let len_before = whitelist.len();
+        whitelist.retain(|address| !addresses.contains(address));
+        let removed = (len_before - whitelist.len()) as u32;
+
+        if removed > 0 {
+            self.realloc_token_info()?;
+        }
+
+        self.emit_batch_event(0, removed);
+        Ok(())
+    }
+
+    fn emit_batch_event(&self, added: u32, removed: u32) {
+        emit!(WhitelistBatchUpdatedEvent {
+            token_mint: self.token_info.token_mint,
+            added,
+            removed,
+            whitelist_len: self.token_info.whitelist_addresses.len() as u32,
+            authority: self.whitelist_manager.key(),
+        });
+    }
+
+    /// Emitted after the change, so `is_listed` reflects the current list
+    fn emit_updated_event(&self, address: Pubkey, was_listed: bool) {
+        emit!(WhitelistUpdatedEvent {
+            token_mint: self.token_info.token_mint,
+            address,
+            was_listed,
+            is_listed: self.token_info.whitelist_addresses.contains(&address),
+            whitelist_len: self.token_info.whitelist_addresses.len() as u32,
+            authority: self.whitelist_manager.key(),
+        });
+    }
+
+    /// Adding only makes sense while a `Whitelist` rule reads the list
+    fn require_whitelist_rule(&self) -> Result<()> {
+        require!(
+            self.rule_set.find(|rule| matches!(rule, Rule::Whitelist { .. })).is_some(),
+            CustomError::WhitelistDisabled
+        );
         Ok(())
     }
-}
\ No newline at end of file
+
+    fn realloc_token_info(&self) -> Result<()> {
+        resize_token_info(
+            &self.token_info,
+            &self.whitelist_manager.to_account_info(),
+            &self.system_program.to_account_info(),
+        )
+    }
+}
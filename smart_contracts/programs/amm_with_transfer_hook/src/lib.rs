@@ -1,17 +1,30 @@
+// Anchor 0.31's IDL handlers, generated by `#[program]` at the crate root,
+// still call `AccountInfo::realloc`. The allow cannot be attached to them, so
+// it is set here and lifted again for every module we write.
+#![allow(deprecated)]
+
 use anchor_lang::prelude::*;
+use spl_discriminator::SplDiscriminate;
+use spl_transfer_hook_interface::instruction::ExecuteInstruction;
 
+#[warn(deprecated)]
 pub mod instructions;
+#[warn(deprecated)]
 pub mod state;
+#[warn(deprecated)]
+pub mod util;
 
 use instructions::*;
 use state::*;
 
 declare_id!("F4RupoT7DMW6dDbkzoyG3R3LndyW9EJEeBp4FvMu9v56");
 
+#[warn(deprecated)]
 #[program]
 pub mod amm_with_transfer_hook {
     use super::*;
 
+    #[allow(clippy::too_many_arguments)]
     pub fn initialize_token_info(
         ctx: Context<InitializeExtraAccountMetaList>,
         token_name: String,
@@ -36,23 +49,279 @@ pub mod amm_with_transfer_hook {
         )
     }
 
-    pub fn add_to_whitelist(ctx: Context<UpdateTokenExtension>, new_address: Pubkey) -> Result<()> {
+    pub fn update_extra_account_meta_list(ctx: Context<UpdateExtraAccountMetaList>) -> Result<()> {
+        ctx.accounts.update_extra_account_meta_list()
+    }
+
+    pub fn migrate_token_info(ctx: Context<MigrateTokenInfo>) -> Result<()> {
+        ctx.accounts.migrate_token_info(ctx.bumps)
+    }
+
+    pub fn add_to_whitelist(ctx: Context<TokenInfoWhitelistOps>, new_address: Pubkey) -> Result<()> {
         ctx.accounts.add_to_whitelist(new_address)
     }
 
-    pub fn set_max_transfer_limit(ctx: Context<UpdateTokenExtension>, limit: u64) -> Result<()> {
+    pub fn remove_from_whitelist(ctx: Context<TokenInfoWhitelistOps>, address: Pubkey) -> Result<()> {
+        ctx.accounts.remove_from_whitelist(address)
+    }
+
+    pub fn add_many_to_whitelist(
+        ctx: Context<TokenInfoWhitelistOps>,
+        addresses: Vec<Pubkey>,
+    ) -> Result<()> {
+        ctx.accounts.add_many_to_whitelist(addresses)
+    }
+
+    pub fn remove_many_from_whitelist(
+        ctx: Context<TokenInfoWhitelistOps>,
+        addresses: Vec<Pubkey>,
+    ) -> Result<()> {
+        ctx.accounts.remove_many_from_whitelist(addresses)
+    }
+
+    pub fn add_whitelist_entry(ctx: Context<AddWhitelistEntry>, wallet: Pubkey) -> Result<()> {
+        ctx.accounts.add_whitelist_entry(ctx.bumps, wallet)
+    }
+
+    pub fn remove_whitelist_entry(ctx: Context<RemoveWhitelistEntry>) -> Result<()> {
+        ctx.accounts.remove_whitelist_entry()
+    }
+
+    pub fn register_whitelist_membership(
+        ctx: Context<RegisterWhitelistMembership>,
+        proof: Vec<[u8; 32]>,
+    ) -> Result<()> {
+        ctx.accounts.register_whitelist_membership(ctx.bumps, proof)
+    }
+
+    pub fn set_whitelist_merkle_root(ctx: Context<UpdateTokenExtension>, root: [u8; 32]) -> Result<()> {
+        ctx.accounts.set_whitelist_merkle_root(root)
+    }
+
+    pub fn set_whitelist_direction(
+        ctx: Context<UpdateRuleSettings>,
+        direction: WhitelistDirection,
+    ) -> Result<()> {
+        ctx.accounts.set_whitelist_direction(direction)
+    }
+
+    pub fn set_whitelist_mode(ctx: Context<UpdateRuleSettings>, mode: WhitelistMode) -> Result<()> {
+        ctx.accounts.set_whitelist_mode(mode)
+    }
+
+    pub fn add_to_denylist(ctx: Context<AddToDenylist>, wallet: Pubkey) -> Result<()> {
+        ctx.accounts.add_to_denylist(ctx.bumps, wallet)
+    }
+
+    pub fn remove_from_denylist(ctx: Context<RemoveFromDenylist>) -> Result<()> {
+        ctx.accounts.remove_from_denylist()
+    }
+
+    pub fn create_lockup(
+        ctx: Context<CreateLockup>,
+        wallet: Pubkey,
+        total_locked: u64,
+        start_time: i64,
+        cliff_time: i64,
+        end_time: i64,
+    ) -> Result<()> {
+        ctx.accounts.create_lockup(ctx.bumps, wallet, total_locked, start_time, cliff_time, end_time)
+    }
+
+    pub fn close_lockup(ctx: Context<CloseLockup>) -> Result<()> {
+        ctx.accounts.close_lockup()
+    }
+
+    pub fn update_lockups(ctx: Context<UpdateRuleSettings>, enable: bool) -> Result<()> {
+        ctx.accounts.update_lockups(enable)
+    }
+
+    pub fn update_whitelist(ctx: Context<UpdateRuleSettings>, enable: bool) -> Result<()> {
+        ctx.accounts.update_whitelist(enable)
+    }
+
+    pub fn update_denylist(ctx: Context<UpdateRuleSettings>, enable: bool) -> Result<()> {
+        ctx.accounts.update_denylist(enable)
+    }
+
+    pub fn initialize_wallet_state(ctx: Context<InitializeWalletState>) -> Result<()> {
+        ctx.accounts.initialize_wallet_state(ctx.bumps)
+    }
+
+    pub fn set_transfer_quota(
+        ctx: Context<UpdateRuleSettings>,
+        enable: bool,
+        quota: u64,
+        window_seconds: i64,
+    ) -> Result<()> {
+        ctx.accounts.set_transfer_quota(enable, quota, window_seconds)
+    }
+
+    pub fn set_transfer_cooldown(
+        ctx: Context<UpdateRuleSettings>,
+        enable: bool,
+        interval: u64,
+        unit: CooldownUnit,
+    ) -> Result<()> {
+        ctx.accounts.set_transfer_cooldown(enable, interval, unit)
+    }
+
+    pub fn set_max_wallet_balance(
+        ctx: Context<UpdateRuleSettings>,
+        enable: bool,
+        amount: u64,
+        mode: ThresholdMode,
+    ) -> Result<()> {
+        ctx.accounts.set_max_wallet_balance(enable, amount, mode)
+    }
+
+    pub fn add_balance_cap_exemption(ctx: Context<ExemptionOps>, address: Pubkey) -> Result<()> {
+        ctx.accounts.add_balance_cap_exemption(address)
+    }
+
+    pub fn remove_balance_cap_exemption(
+        ctx: Context<ExemptionOps>,
+        address: Pubkey,
+    ) -> Result<()> {
+        ctx.accounts.remove_balance_cap_exemption(address)
+    }
+
+    pub fn set_launch_schedule(
+        ctx: Context<UpdateTokenExtension>,
+        trading_opens_at: i64,
+        launch_phase_duration: i64,
+        launch_max_transfer_amount: u64,
+        launch_max_wallet_balance: u64,
+    ) -> Result<()> {
+        ctx.accounts.set_launch_schedule(
+            trading_opens_at,
+            launch_phase_duration,
+            launch_max_transfer_amount,
+            launch_max_wallet_balance,
+        )
+    }
+
+    pub fn add_launch_exemption(ctx: Context<ExemptionOps>, wallet: Pubkey) -> Result<()> {
+        ctx.accounts.add_launch_exemption(wallet)
+    }
+
+    pub fn remove_launch_exemption(ctx: Context<ExemptionOps>, wallet: Pubkey) -> Result<()> {
+        ctx.accounts.remove_launch_exemption(wallet)
+    }
+
+    pub fn propose_admin(ctx: Context<UpdateTokenExtension>, new_admin: Pubkey) -> Result<()> {
+        ctx.accounts.propose_admin(new_admin)
+    }
+
+    pub fn accept_admin(ctx: Context<AcceptAdmin>) -> Result<()> {
+        ctx.accounts.accept_admin()
+    }
+
+    pub fn cancel_admin_transfer(ctx: Context<UpdateTokenExtension>) -> Result<()> {
+        ctx.accounts.cancel_admin_transfer()
+    }
+
+    pub fn renounce_admin(ctx: Context<UpdateTokenExtension>) -> Result<()> {
+        ctx.accounts.renounce_admin()
+    }
+
+    pub fn pause(ctx: Context<PauseOps>) -> Result<()> {
+        ctx.accounts.pause()
+    }
+
+    pub fn unpause(ctx: Context<PauseOps>) -> Result<()> {
+        ctx.accounts.unpause()
+    }
+
+    pub fn set_role(ctx: Context<UpdateTokenExtension>, role: Role, holder: Pubkey) -> Result<()> {
+        ctx.accounts.set_role(role, holder)
+    }
+
+    pub fn set_pause_authority(ctx: Context<UpdateTokenExtension>, pause_authority: Pubkey) -> Result<()> {
+        ctx.accounts.set_pause_authority(pause_authority)
+    }
+
+    pub fn add_pause_exemption(ctx: Context<ExemptionOps>, wallet: Pubkey) -> Result<()> {
+        ctx.accounts.add_pause_exemption(wallet)
+    }
+
+    pub fn remove_pause_exemption(ctx: Context<ExemptionOps>, wallet: Pubkey) -> Result<()> {
+        ctx.accounts.remove_pause_exemption(wallet)
+    }
+
+    pub fn set_timelock_delay(ctx: Context<UpdateTokenExtension>, delay: i64) -> Result<()> {
+        ctx.accounts.set_timelock_delay(delay)
+    }
+
+    pub fn queue_change(ctx: Context<QueueChange>, change: SettingChange) -> Result<()> {
+        ctx.accounts.queue_change(ctx.bumps, change)
+    }
+
+    pub fn execute_change(ctx: Context<ExecuteChange>, change_id: u64) -> Result<()> {
+        ctx.accounts.execute_change(change_id)
+    }
+
+    pub fn cancel_change(ctx: Context<CancelChange>, change_id: u64) -> Result<()> {
+        ctx.accounts.cancel_change(change_id)
+    }
+
+    pub fn add_rule(ctx: Context<RuleSetOps>, rule: Rule) -> Result<()> {
+        ctx.accounts.add_rule(rule)
+    }
+
+    pub fn remove_rule(ctx: Context<RuleSetOps>, index: u32) -> Result<()> {
+        ctx.accounts.remove_rule(index)
+    }
+
+    pub fn move_rule(ctx: Context<RuleSetOps>, from: u32, to: u32) -> Result<()> {
+        ctx.accounts.move_rule(from, to)
+    }
+
+    pub fn initialize_transfer_stats(ctx: Context<InitializeTransferStats>) -> Result<()> {
+        ctx.accounts.initialize_transfer_stats(ctx.bumps)
+    }
+
+    pub fn close_transfer_stats(ctx: Context<CloseTransferStats>) -> Result<()> {
+        ctx.accounts.close_transfer_stats()
+    }
+
+    pub fn set_max_transfer_limit(ctx: Context<UpdateRuleSettings>, limit: u64) -> Result<()> {
         ctx.accounts.set_max_transfer_limit(limit)
     }
 
+    pub fn set_transfer_limit_mode(ctx: Context<UpdateRuleSettings>, mode: ThresholdMode) -> Result<()> {
+        ctx.accounts.set_transfer_limit_mode(mode)
+    }
+
+    pub fn set_max_transfer_limit_schedule(
+        ctx: Context<UpdateRuleSettings>,
+        schedule: TransferLimitSchedule,
+    ) -> Result<()> {
+        ctx.accounts.set_max_transfer_limit_schedule(schedule)
+    }
+
     pub fn update_whale_alert(
-        ctx: Context<UpdateTokenExtension>,
+        ctx: Context<UpdateRuleSettings>,
         enable: bool,
         amount: u64,
     ) -> Result<()> {
         ctx.accounts.update_whale_alert(enable, amount)
     }
 
+    pub fn set_whale_alert_mode(ctx: Context<UpdateRuleSettings>, mode: ThresholdMode) -> Result<()> {
+        ctx.accounts.set_whale_alert_mode(mode)
+    }
+
+    pub fn set_whale_tiers(ctx: Context<UpdateRuleSettings>, thresholds: Vec<u64>) -> Result<()> {
+        ctx.accounts.set_whale_tiers(thresholds)
+    }
+
+    /// Invoked by Token-2022 on every `transfer_checked` of a mint whose
+    /// TransferHook extension points at this program. Uses the SPL
+    /// transfer-hook interface `Execute` discriminator so the token program
+    /// (and any client resolving the interface) can reach it directly.
+    #[instruction(discriminator = ExecuteInstruction::SPL_DISCRIMINATOR_SLICE)]
     pub fn transfer_hook(ctx: Context<TransferHook>, amount: u64) -> Result<()> {
-        ctx.accounts.transfer_hook(amount)
+        ctx.accounts.transfer_hook(amount, ctx.remaining_accounts)
     }
 }
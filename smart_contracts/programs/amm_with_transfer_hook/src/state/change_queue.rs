@@ -0,0 +1,152 @@
+use anchor_lang::prelude::*;
+
+use crate::state::{
+    CooldownUnit, Role, Rule, ThresholdMode, TransferLimitSchedule, WhitelistDirection,
+    WhitelistMode,
+};
+
+pub const MAX_QUEUED_CHANGES: usize = 8;
+
+/// Sensitive setting that must wait out `TokenInfo::timelock_delay` while the
+/// timelock is on
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, PartialEq, Eq, Debug)]
+pub enum SettingChange {
+    MaxTransferLimit { limit: u64, mode: ThresholdMode },
+    MaxTransferLimitSchedule { schedule: TransferLimitSchedule },
+    TransferQuota { enable: bool, quota: u64, window: i64 },
+    TransferCooldown { enable: bool, interval: u64, unit: CooldownUnit },
+    MaxWalletBalance { enable: bool, amount: u64, mode: ThresholdMode },
+    LaunchSchedule {
+        trading_opens_at: i64,
+        launch_phase_duration: i64,
+        launch_max_transfer_amount: u64,
+        launch_max_wallet_balance: u64,
+    },
+    WhitelistEnabled { enable: bool },
+    WhitelistMode { mode: WhitelistMode },
+    WhitelistDirection { direction: WhitelistDirection },
+    DenylistEnabled { enable: bool },
+    LockupsEnabled { enable: bool },
+    /// Executed by `add_to_denylist`, which creates the entry account
+    DenylistWallet { wallet: Pubkey },
+    /// Executed by `create_lockup`, which creates the lockup account
+    Lockup {
+        wallet: Pubkey,
+        total_locked: u64,
+        start_time: i64,
+        cliff_time: i64,
+        end_time: i64,
+    },
+    /// Executed by `add_rule`; only rules that restrict transfers need it
+    AddRule { rule: Rule },
+    /// Optional: the pauser may also pause directly while the timelock is on
+    Paused { paused: bool },
+    /// Lowering the delay is itself timelocked; raising it is immediate
+    TimelockDelay { delay: i64 },
+}
+
+impl SettingChange {
+    /// `AddRule` is the largest variant
+    pub const SIZE: usize = 1 + Rule::MAX_SIZE;
+
+    /// Role that may queue, cancel and execute this change
+    pub fn required_role(&self) -> Role {
+        match self {
+            SettingChange::MaxTransferLimit { .. }
+            | SettingChange::MaxTransferLimitSchedule { .. }
+            | SettingChange::TransferQuota { .. }
+            | SettingChange::TransferCooldown { .. }
+            | SettingChange::MaxWalletBalance { .. }
+            | SettingChange::LaunchSchedule { .. }
+            | SettingChange::LockupsEnabled { .. }
+            | SettingChange::Lockup { .. } => Role::LimitsManager,
+            SettingChange::WhitelistEnabled { .. }
+            | SettingChange::WhitelistMode { .. }
+            | SettingChange::WhitelistDirection { .. }
+            | SettingChange::DenylistEnabled { .. }
+            | SettingChange::DenylistWallet { .. } => Role::WhitelistManager,
+            SettingChange::AddRule { rule } => rule.required_role(),
+            SettingChange::Paused { .. } => Role::Pauser,
+            SettingChange::TimelockDelay { .. } => Role::RoleAdmin,
+        }
+    }
+
+    /// Changes after which the extra account meta list must be rewritten
+    pub fn changes_rule_accounts(&self) -> bool {
+        match self {
+            SettingChange::TransferQuota { .. }
+            | SettingChange::TransferCooldown { .. }
+            | SettingChange::WhitelistEnabled { .. }
+            | SettingChange::WhitelistMode { .. }
+            | SettingChange::WhitelistDirection { .. }
+            | SettingChange::DenylistEnabled { .. }
+            | SettingChange::LockupsEnabled { .. }
+            | SettingChange::AddRule { .. } => true,
+            SettingChange::MaxTransferLimit { .. }
+            | SettingChange::MaxTransferLimitSchedule { .. }
+            | SettingChange::MaxWalletBalance { .. }
+            | SettingChange::LaunchSchedule { .. }
+            | SettingChange::DenylistWallet { .. }
+            | SettingChange::Lockup { .. }
+            | SettingChange::Paused { .. }
+            | SettingChange::TimelockDelay { .. } => false,
+        }
+    }
+
+    /// Changes only their own instruction can execute: `add_to_denylist` and
+    /// `create_lockup` create the account, `add_rule` owns rule additions
+    pub fn has_own_instruction(&self) -> bool {
+        matches!(
+            self,
+            SettingChange::DenylistWallet { .. }
+                | SettingChange::Lockup { .. }
+                | SettingChange::AddRule { .. }
+        )
+    }
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, PartialEq, Eq, Debug)]
+pub struct QueuedChange {
+    pub id: u64,
+    pub change: SettingChange,
+    pub queued_by: Pubkey,
+    /// Unix timestamp from which `execute_change` accepts it
+    pub execute_after: i64,
+}
+
+impl QueuedChange {
+    pub const SIZE: usize = 8 + SettingChange::SIZE + 32 + 8;
+}
+
+/// Pending timelocked changes of `token_mint`. Lives at `["change-queue", mint]`.
+#[account]
+pub struct ChangeQueue {
+    pub token_mint: Pubkey,
+    pub next_id: u64,
+    pub changes: Vec<QueuedChange>,
+    pub bump: u8,
+}
+
+impl ChangeQueue {
+    pub const INIT_SPACE: usize = 8 + 32 + 8 + 4 + (QueuedChange::SIZE * MAX_QUEUED_CHANGES) + 1;
+
+    /// Removes and returns the change with `id`
+    pub fn take(&mut self, id: u64) -> Option<QueuedChange> {
+        let pos = self.changes.iter().position(|queued| queued.id == id)?;
+        Some(self.changes.remove(pos))
+    }
+
+    pub fn get(&self, id: u64) -> Option<&QueuedChange> {
+        self.changes.iter().find(|queued| queued.id == id)
+    }
+
+    /// Removes and returns the oldest change equal to `change` that may be
+    /// executed at `now`
+    pub fn take_matured(&mut self, change: &SettingChange, now: i64) -> Option<QueuedChange> {
+        let pos = self
+            .changes
+            .iter()
+            .position(|queued| queued.change == *change && now >= queued.execute_after)?;
+        Some(self.changes.remove(pos))
+    }
+}
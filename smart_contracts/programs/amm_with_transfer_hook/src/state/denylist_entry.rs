@@ -0,0 +1,14 @@
+use anchor_lang::prelude::*;
+
+/// Blocks `wallet` from sending or receiving `token_mint` while the denylist
+/// is enabled. Lives at `["denylist", mint, wallet]`.
+#[account]
+pub struct DenylistEntry {
+    pub token_mint: Pubkey,
+    pub wallet: Pubkey,
+    pub bump: u8,
+}
+
+impl DenylistEntry {
+    pub const INIT_SPACE: usize = 8 + 32 + 32 + 1;
+}
@@ -2,7 +2,7 @@ use anchor_lang::prelude::*;
 
 #[error_code]
 pub enum CustomError {
-    #[msg("TransferHook: Owner not whitelisted")]
+    #[msg("TransferHook: Owner or destination owner not whitelisted")]
     NotWhitelisted,
     #[msg("TransferHook: Transfer amount exceeds allowed maximum")]
     TransferLimitExceeded,
@@ -10,4 +10,82 @@ pub enum CustomError {
     NotInTransferHook,
     #[msg("TransferHook: Whitelist is disabled")]
     WhitelistDisabled,
-}
\ No newline at end of file
+    #[msg("TransferHook: Signer is neither the mint authority nor the transfer hook authority")]
+    UnauthorizedInitializer,
+    #[msg("TransferHook: Whitelist is full")]
+    WhitelistFull,
+    #[msg("TransferHook: Rule account missing, update the extra account meta list")]
+    MissingRuleAccount,
+    #[msg("TransferHook: Whitelist is not in Merkle mode")]
+    NotMerkleWhitelist,
+    #[msg("TransferHook: Invalid Merkle proof")]
+    InvalidMerkleProof,
+    #[msg("TransferHook: Owner or destination owner is denylisted")]
+    Denylisted,
+    #[msg("TransferHook: Wallet state account is not initialized")]
+    WalletStateNotInitialized,
+    #[msg("TransferHook: Transfer quota for the current window exceeded")]
+    TransferQuotaExceeded,
+    #[msg("TransferHook: Quota window must be positive and at most MAX_QUOTA_WINDOW")]
+    InvalidQuotaWindow,
+    #[msg("TransferHook: Wallet is in transfer cooldown")]
+    CooldownActive,
+    #[msg("TransferHook: Destination balance would exceed the maximum wallet balance")]
+    MaxWalletBalanceExceeded,
+    #[msg("TransferHook: Exemption list is full")]
+    ExemptionListFull,
+    #[msg("TransferHook: Basis points must not exceed 10000")]
+    InvalidBasisPoints,
+    #[msg("TransferHook: Trading is not open yet")]
+    TradingNotOpen,
+    #[msg("TransferHook: Transfer amount exceeds the launch phase maximum")]
+    LaunchTransferLimitExceeded,
+    #[msg("TransferHook: Destination balance would exceed the launch phase maximum")]
+    LaunchWalletBalanceExceeded,
+    #[msg("TransferHook: Launch phase duration must not be negative")]
+    InvalidLaunchSchedule,
+    #[msg("TransferHook: Limit schedule needs a positive duration and must not tighten")]
+    InvalidLimitSchedule,
+    #[msg("TransferHook: Transfer would spend tokens that are still locked")]
+    TokensLocked,
+    #[msg("TransferHook: Lockup needs start <= cliff <= end and start < end")]
+    InvalidLockupSchedule,
+    #[msg("TransferHook: Transfers are paused")]
+    TransfersPaused,
+    #[msg("TransferHook: Transfers are already paused")]
+    AlreadyPaused,
+    #[msg("TransferHook: Transfers are not paused")]
+    NotPaused,
+    #[msg("TransferHook: Signer is not the pending admin")]
+    NotPendingAdmin,
+    #[msg("TransferHook: No admin transfer is pending")]
+    NoPendingAdmin,
+    #[msg("TransferHook: Signer does not hold the required role")]
+    MissingRole,
+    #[msg("TransferHook: Change must go through the timelock queue")]
+    TimelockRequired,
+    #[msg("TransferHook: Timelock delay has not elapsed")]
+    TimelockNotElapsed,
+    #[msg("TransferHook: Change queue is full")]
+    ChangeQueueFull,
+    #[msg("TransferHook: Queued change not found")]
+    QueuedChangeNotFound,
+    #[msg("TransferHook: Timelock delay must not be negative")]
+    InvalidTimelockDelay,
+    #[msg("TransferHook: Rule set is full")]
+    RuleSetFull,
+    #[msg("TransferHook: Invalid rule parameters")]
+    InvalidRule,
+    #[msg("TransferHook: Rule index out of bounds")]
+    RuleIndexOutOfBounds,
+    #[msg("TransferHook: Whale thresholds must be 1 to MAX_WHALE_TIERS ascending values")]
+    InvalidWhaleTiers,
+    #[msg("TransferHook: Execute this change with the instruction it belongs to")]
+    ChangeHasOwnInstruction,
+    #[msg("TransferHook: Unpause transfers before renouncing admin")]
+    RenounceWhilePaused,
+    #[msg("TransferHook: TokenInfo is already on the current layout")]
+    AlreadyMigrated,
+    #[msg("TransferHook: Enable this rule before changing its settings")]
+    RuleNotConfigured,
+}
@@ -1,7 +1,346 @@
 use anchor_lang::prelude::*;
 
+use crate::state::{
+    CooldownUnit, ExemptionList, Role, Rule, SettingChange, ThresholdMode, TransferFacts, TransferLimitSchedule,
+    WhitelistDirection, WhitelistMode, BASIS_POINTS_DENOMINATOR,
+};
+
 #[event]
 pub struct WhaleTransferEvent {
     pub whale_address: Pubkey,
     pub transfer_amount: u64,
-}
\ No newline at end of file
+    /// Highest tier crossed, counting from 1 for the lowest threshold
+    pub tier: u8,
+    pub token_mint: Pubkey,
+    pub source_token: Pubkey,
+    pub destination_token: Pubkey,
+    pub destination_owner: Pubkey,
+    pub slot: u64,
+    pub unix_timestamp: i64,
+    /// `transfer_amount` in basis points of the mint's supply at transfer time
+    pub supply_basis_points: u64,
+}
+
+impl WhaleTransferEvent {
+    pub fn new(transfer: &TransferFacts, tier: u8) -> Self {
+        let supply_basis_points = (transfer.amount as u128 * BASIS_POINTS_DENOMINATOR as u128)
+            .checked_div(transfer.supply as u128)
+            .unwrap_or(0) as u64;
+
+        Self {
+            whale_address: transfer.owner,
+            transfer_amount: transfer.amount,
+            tier,
+            token_mint: transfer.mint,
+            source_token: transfer.source_token,
+            destination_token: transfer.destination_token,
+            destination_owner: transfer.destination_owner,
+            slot: transfer.clock.slot,
+            unix_timestamp: transfer.clock.unix_timestamp,
+            supply_basis_points,
+        }
+    }
+}
+
+#[event]
+pub struct PausedEvent {
+    pub token_mint: Pubkey,
+    pub pause_authority: Pubkey,
+}
+
+#[event]
+pub struct UnpausedEvent {
+    pub token_mint: Pubkey,
+    pub pause_authority: Pubkey,
+}
+
+#[event]
+pub struct WhitelistBatchUpdatedEvent {
+    pub token_mint: Pubkey,
+    pub added: u32,
+    pub removed: u32,
+    pub whitelist_len: u32,
+    pub authority: Pubkey,
+}
+
+#[event]
+pub struct ChangeQueuedEvent {
+    pub token_mint: Pubkey,
+    pub change_id: u64,
+    pub change: SettingChange,
+    pub queued_by: Pubkey,
+    pub execute_after: i64,
+}
+
+#[event]
+pub struct ChangeExecutedEvent {
+    pub token_mint: Pubkey,
+    pub change_id: u64,
+    pub change: SettingChange,
+    pub executed_by: Pubkey,
+}
+
+#[event]
+pub struct ChangeCancelledEvent {
+    pub token_mint: Pubkey,
+    pub change_id: u64,
+    pub change: SettingChange,
+    pub cancelled_by: Pubkey,
+}
+
+/// Rule whose on/off switch changed in a `RuleToggledEvent`
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq, Debug)]
+pub enum ToggledRule {
+    Whitelist,
+    Denylist,
+    Lockups,
+}
+
+#[event]
+pub struct WhitelistUpdatedEvent {
+    pub token_mint: Pubkey,
+    pub address: Pubkey,
+    pub was_listed: bool,
+    pub is_listed: bool,
+    pub whitelist_len: u32,
+    pub authority: Pubkey,
+}
+
+/// Emitted for `WhitelistEntry` accounts, including self-registration with a
+/// Merkle proof, where `authority` is the registering wallet
+#[event]
+pub struct WhitelistEntryUpdatedEvent {
+    pub token_mint: Pubkey,
+    pub wallet: Pubkey,
+    pub was_listed: bool,
+    pub is_listed: bool,
+    pub authority: Pubkey,
+}
+
+#[event]
+pub struct DenylistUpdatedEvent {
+    pub token_mint: Pubkey,
+    pub wallet: Pubkey,
+    pub was_listed: bool,
+    pub is_listed: bool,
+    pub authority: Pubkey,
+}
+
+#[event]
+pub struct ExemptionUpdatedEvent {
+    pub token_mint: Pubkey,
+    pub list: ExemptionList,
+    pub address: Pubkey,
+    pub was_exempt: bool,
+    pub is_exempt: bool,
+    pub list_len: u32,
+    pub authority: Pubkey,
+}
+
+#[event]
+pub struct LockupCreatedEvent {
+    pub token_mint: Pubkey,
+    pub wallet: Pubkey,
+    pub total_locked: u64,
+    pub start_time: i64,
+    pub cliff_time: i64,
+    pub end_time: i64,
+    pub authority: Pubkey,
+}
+
+#[event]
+pub struct LockupClosedEvent {
+    pub token_mint: Pubkey,
+    pub wallet: Pubkey,
+    pub total_locked: u64,
+    /// Amount that was still locked and is released by the close
+    pub released_amount: u64,
+    pub authority: Pubkey,
+}
+
+#[event]
+pub struct TransferStatsToggledEvent {
+    pub token_mint: Pubkey,
+    pub old_enabled: bool,
+    pub new_enabled: bool,
+    pub authority: Pubkey,
+}
+
+#[event]
+pub struct RuleToggledEvent {
+    pub token_mint: Pubkey,
+    pub rule: ToggledRule,
+    pub old_enabled: bool,
+    pub new_enabled: bool,
+    pub authority: Pubkey,
+}
+
+#[event]
+pub struct WhitelistModeChangedEvent {
+    pub token_mint: Pubkey,
+    pub old_mode: WhitelistMode,
+    pub new_mode: WhitelistMode,
+    pub authority: Pubkey,
+}
+
+#[event]
+pub struct WhitelistDirectionChangedEvent {
+    pub token_mint: Pubkey,
+    pub old_direction: WhitelistDirection,
+    pub new_direction: WhitelistDirection,
+    pub authority: Pubkey,
+}
+
+#[event]
+pub struct WhitelistMerkleRootChangedEvent {
+    pub token_mint: Pubkey,
+    pub old_root: [u8; 32],
+    pub new_root: [u8; 32],
+    pub authority: Pubkey,
+}
+
+#[event]
+pub struct TransferLimitChangedEvent {
+    pub token_mint: Pubkey,
+    pub old_enabled: bool,
+    pub new_enabled: bool,
+    pub old_limit: u64,
+    pub new_limit: u64,
+    pub old_mode: ThresholdMode,
+    pub new_mode: ThresholdMode,
+    pub old_schedule: Option<TransferLimitSchedule>,
+    pub new_schedule: Option<TransferLimitSchedule>,
+    pub authority: Pubkey,
+}
+
+#[event]
+pub struct TransferQuotaChangedEvent {
+    pub token_mint: Pubkey,
+    pub old_enabled: bool,
+    pub new_enabled: bool,
+    pub old_quota: u64,
+    pub new_quota: u64,
+    pub old_window: i64,
+    pub new_window: i64,
+    pub authority: Pubkey,
+}
+
+#[event]
+pub struct CooldownChangedEvent {
+    pub token_mint: Pubkey,
+    pub old_enabled: bool,
+    pub new_enabled: bool,
+    pub old_interval: u64,
+    pub new_interval: u64,
+    pub old_unit: CooldownUnit,
+    pub new_unit: CooldownUnit,
+    pub authority: Pubkey,
+}
+
+#[event]
+pub struct MaxWalletBalanceChangedEvent {
+    pub token_mint: Pubkey,
+    pub old_enabled: bool,
+    pub new_enabled: bool,
+    pub old_amount: u64,
+    pub new_amount: u64,
+    pub old_mode: ThresholdMode,
+    pub new_mode: ThresholdMode,
+    pub authority: Pubkey,
+}
+
+#[event]
+pub struct LaunchScheduleChangedEvent {
+    pub token_mint: Pubkey,
+    pub old_trading_opens_at: i64,
+    pub new_trading_opens_at: i64,
+    pub old_launch_phase_duration: i64,
+    pub new_launch_phase_duration: i64,
+    pub old_launch_max_transfer_amount: u64,
+    pub new_launch_max_transfer_amount: u64,
+    pub old_launch_max_wallet_balance: u64,
+    pub new_launch_max_wallet_balance: u64,
+    pub authority: Pubkey,
+}
+
+#[event]
+pub struct WhaleAlertChangedEvent {
+    pub token_mint: Pubkey,
+    pub old_enabled: bool,
+    pub new_enabled: bool,
+    pub old_thresholds: Vec<u64>,
+    pub new_thresholds: Vec<u64>,
+    pub old_mode: ThresholdMode,
+    pub new_mode: ThresholdMode,
+    pub authority: Pubkey,
+}
+
+#[event]
+pub struct RuleAddedEvent {
+    pub token_mint: Pubkey,
+    pub index: u32,
+    pub rule: Rule,
+    pub authority: Pubkey,
+}
+
+#[event]
+pub struct RuleRemovedEvent {
+    pub token_mint: Pubkey,
+    pub index: u32,
+    pub rule: Rule,
+    pub authority: Pubkey,
+}
+
+#[event]
+pub struct RuleMovedEvent {
+    pub token_mint: Pubkey,
+    pub old_index: u32,
+    pub new_index: u32,
+    pub rule: Rule,
+    pub authority: Pubkey,
+}
+
+#[event]
+pub struct RoleChangedEvent {
+    pub token_mint: Pubkey,
+    pub role: Role,
+    pub old_holder: Pubkey,
+    pub new_holder: Pubkey,
+    pub authority: Pubkey,
+}
+
+#[event]
+pub struct TimelockDelayChangedEvent {
+    pub token_mint: Pubkey,
+    pub old_delay: i64,
+    pub new_delay: i64,
+    pub authority: Pubkey,
+}
+
+#[event]
+pub struct AdminProposedEvent {
+    pub token_mint: Pubkey,
+    pub old_pending_admin: Option<Pubkey>,
+    pub new_pending_admin: Pubkey,
+    pub authority: Pubkey,
+}
+
+#[event]
+pub struct AdminTransferCancelledEvent {
+    pub token_mint: Pubkey,
+    pub cancelled_admin: Pubkey,
+    pub authority: Pubkey,
+}
+
+#[event]
+pub struct AdminAcceptedEvent {
+    pub token_mint: Pubkey,
+    pub old_admin: Pubkey,
+    pub new_admin: Pubkey,
+}
+
+#[event]
+pub struct AdminRenouncedEvent {
+    pub token_mint: Pubkey,
+    pub old_admin: Pubkey,
+}
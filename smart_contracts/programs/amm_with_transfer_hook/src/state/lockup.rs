@@ -0,0 +1,75 @@
+use anchor_lang::prelude::*;
+
+/// Vesting schedule for `wallet`'s allocation of `token_mint`: nothing vests
+/// before `cliff_time`, then `total_locked` unlocks linearly from
+/// `start_time` to `end_time`. Enforced per debited token account, so the
+/// allocation must stay in one token account. Lives at `["lockup", mint, wallet]`.
+#[account]
+pub struct Lockup {
+    pub token_mint: Pubkey,
+    pub wallet: Pubkey,
+    pub total_locked: u64,
+    pub start_time: i64,
+    pub cliff_time: i64,
+    pub end_time: i64,
+    pub bump: u8,
+}
+
+impl Lockup {
+    pub const INIT_SPACE: usize = 8 + 32 + 32 + 8 + 8 + 8 + 8 + 1;
+
+    pub fn is_valid_schedule(start_time: i64, cliff_time: i64, end_time: i64) -> bool {
+        start_time <= cliff_time && cliff_time <= end_time && start_time < end_time
+    }
+
+    /// Amount that is still locked at `now`
+    pub fn locked_amount(&self, now: i64) -> u64 {
+        if now < self.cliff_time {
+            return self.total_locked;
+        }
+        if now >= self.end_time {
+            return 0;
+        }
+
+        let elapsed = (now - self.start_time) as u128;
+        let duration = (self.end_time - self.start_time) as u128;
+        let vested = self.total_locked as u128 * elapsed / duration;
+        self.total_locked - vested as u64
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const START: i64 = 1_700_000_000;
+    const CLIFF: i64 = START + 100;
+    const END: i64 = START + 1_000;
+
+    fn lockup() -> Lockup {
+        Lockup {
+            token_mint: Pubkey::default(),
+            wallet: Pubkey::default(),
+            total_locked: 10_000,
+            start_time: START,
+            cliff_time: CLIFF,
+            end_time: END,
+            bump: 0,
+        }
+    }
+
+    #[test]
+    fn keeps_everything_locked_before_the_cliff() {
+        assert_eq!(lockup().locked_amount(START - 1), 10_000);
+        assert_eq!(lockup().locked_amount(CLIFF - 1), 10_000);
+    }
+
+    #[test]
+    fn vests_from_the_start_time_once_the_cliff_passes() {
+        let lockup = lockup();
+        assert_eq!(lockup.locked_amount(CLIFF), 9_000);
+        assert_eq!(lockup.locked_amount(START + 500), 5_000);
+        assert_eq!(lockup.locked_amount(END - 1), 10);
+        assert_eq!(lockup.locked_amount(END), 0);
+    }
+}
@@ -1,7 +1,21 @@
 pub mod errors;
 pub mod token_info;
 pub mod events;
+pub mod whitelist_entry;
+pub mod denylist_entry;
+pub mod wallet_state;
+pub mod lockup;
+pub mod change_queue;
+pub mod rule_set;
+pub mod transfer_stats;
 
 pub use errors::*;
 pub use token_info::*;
-pub use events::*;
\ No newline at end of file
+pub use events::*;
+pub use whitelist_entry::*;
+pub use denylist_entry::*;
+pub use wallet_state::*;
+pub use lockup::*;
+pub use change_queue::*;
+pub use rule_set::*;
+pub use transfer_stats::*;
@@ -0,0 +1,363 @@
+use anchor_lang::prelude::*;
+
+use crate::state::{
+    CooldownUnit, CustomError, Role, ThresholdMode, TransferLimitSchedule, WalletState,
+    WhitelistDirection, MAX_QUOTA_WINDOW, MAX_WHALE_TIERS,
+};
+
+pub const MAX_RULES: usize = 16;
+pub const MAX_RULE_WALLETS: usize = 32;
+
+/// A single check in a mint's `RuleSet`, which holds every check the hook
+/// runs apart from the pause switch and the launch schedule. Adding a rule
+/// type means adding a variant here, its arms in the `impl` below and, if it
+/// reads an account, its entry in `extra_account_metas`.
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, PartialEq, Eq, Debug)]
+pub enum Rule {
+    /// The source and/or destination owner must be on the token's whitelist,
+    /// stored as `TokenInfo::whitelist_mode` says
+    Whitelist { direction: WhitelistDirection },
+    /// Neither owner may have a `DenylistEntry`
+    Denylist,
+    /// The source owner must be one of `wallets`
+    AllowedSenders { wallets: Vec<Pubkey> },
+    /// The destination owner must be one of `wallets`
+    AllowedRecipients { wallets: Vec<Pubkey> },
+    /// Neither side of the transfer may be one of `wallets`
+    BlockedWallets { wallets: Vec<Pubkey> },
+    /// Caps a single transfer, in base units or basis points of live supply
+    MaxTransferAmount { limit: u64, mode: ThresholdMode },
+    /// Caps a single transfer with a limit that loosens over time and lifts
+    /// once the schedule ends
+    DecayingTransferLimit { schedule: TransferLimitSchedule },
+    /// Caps the destination balance after the transfer, unless the
+    /// destination is in `TokenInfo::balance_cap_exemptions`
+    MaxWalletBalance { limit: u64, mode: ThresholdMode },
+    /// Caps how much the source owner may send in any rolling `window`
+    /// seconds; needs the owner's `WalletState`
+    TransferQuota { quota: u64, window: i64 },
+    /// Minimum gap between two outbound transfers of the same wallet; needs
+    /// the owner's `WalletState`
+    Cooldown { interval: u64, unit: CooldownUnit },
+    /// The source balance must stay above the source owner's still-locked
+    /// `Lockup`
+    Lockups,
+    /// Flags transfers that reach the ascending `thresholds`. The hook emits a
+    /// single `WhaleTransferEvent` per transfer, tagged with the highest tier
+    /// any `WhaleAlert` rule reports.
+    WhaleAlert { thresholds: Vec<u64>, mode: ThresholdMode },
+}
+
+/// What a rule can see about the transfer being checked
+pub struct TransferFacts<'a> {
+    pub mint: Pubkey,
+    pub source_token: Pubkey,
+    pub owner: Pubkey,
+    pub destination_token: Pubkey,
+    pub destination_owner: Pubkey,
+    pub amount: u64,
+    /// Source balance with `amount` already debited
+    pub source_balance: u64,
+    /// Destination balance with `amount` already credited
+    pub destination_balance: u64,
+    /// Whether the destination token account or its owner skips balance caps
+    pub balance_cap_exempt: bool,
+    pub supply: u64,
+    pub clock: &'a Clock,
+}
+
+/// Per-wallet accounts a rule may read, looked up by the hook among the
+/// extra accounts of the transfer
+pub trait RuleAccounts {
+    fn is_whitelisted(&self, wallet: &Pubkey) -> Result<bool>;
+    fn is_denylisted(&self, wallet: &Pubkey) -> Result<bool>;
+    /// Amount of the source owner's `Lockup` still locked at `now`; 0 without one
+    fn locked_amount(&self, now: i64) -> Result<u64>;
+}
+
+impl Rule {
+    /// Serialized size of the largest rule, a wallet list at `MAX_RULE_WALLETS`
+    pub const MAX_SIZE: usize = 1 + 4 + (32 * MAX_RULE_WALLETS);
+
+    /// Role that may add, remove or move this rule
+    pub fn required_role(&self) -> Role {
+        match self {
+            Rule::Whitelist { .. }
+            | Rule::Denylist
+            | Rule::AllowedSenders { .. }
+            | Rule::AllowedRecipients { .. }
+            | Rule::BlockedWallets { .. } => Role::WhitelistManager,
+            Rule::MaxTransferAmount { .. }
+            | Rule::DecayingTransferLimit { .. }
+            | Rule::MaxWalletBalance { .. }
+            | Rule::TransferQuota { .. }
+            | Rule::Cooldown { .. }
+            | Rule::Lockups
+            | Rule::WhaleAlert { .. } => Role::LimitsManager,
+        }
+    }
+
+    /// Whether adding this rule can block transfers, which the timelock
+    /// must not let happen instantly
+    pub fn restricts_transfers(&self) -> bool {
+        !matches!(self, Rule::WhaleAlert { .. })
+    }
+
+    pub fn uses_wallet_state(&self) -> bool {
+        matches!(self, Rule::TransferQuota { .. } | Rule::Cooldown { .. })
+    }
+
+    /// Both forms of the per-transfer limit, which the limit setters
+    /// replace with each other
+    pub fn is_transfer_limit(&self) -> bool {
+        matches!(
+            self,
+            Rule::MaxTransferAmount { .. } | Rule::DecayingTransferLimit { .. }
+        )
+    }
+
+    pub fn validate(&self) -> Result<()> {
+        match self {
+            Rule::AllowedSenders { wallets }
+            | Rule::AllowedRecipients { wallets }
+            | Rule::BlockedWallets { wallets } => {
+                require!(wallets.len() <= MAX_RULE_WALLETS, CustomError::InvalidRule);
+            }
+            Rule::MaxTransferAmount { limit, mode } | Rule::MaxWalletBalance { limit, mode } => {
+                require!(mode.is_valid(*limit), CustomError::InvalidBasisPoints);
+            }
+            Rule::DecayingTransferLimit { schedule } => {
+                require!(schedule.is_valid(), CustomError::InvalidLimitSchedule);
+            }
+            Rule::TransferQuota { window, .. } => {
+                require!(
+                    (1..=MAX_QUOTA_WINDOW).contains(window),
+                    CustomError::InvalidQuotaWindow
+                );
+            }
+            Rule::WhaleAlert { thresholds, mode } => {
+                require!(
+                    !thresholds.is_empty()
+                        && thresholds.len() <= MAX_WHALE_TIERS
+                        && thresholds.windows(2).all(|pair| pair[0] < pair[1]),
+                    CustomError::InvalidWhaleTiers
+                );
+                require!(
+                    thresholds.iter().all(|&threshold| mode.is_valid(threshold)),
+                    CustomError::InvalidBasisPoints
+                );
+            }
+            Rule::Whitelist { .. } | Rule::Denylist | Rule::Cooldown { .. } | Rule::Lockups => {}
+        }
+        Ok(())
+    }
+
+    /// Rejects the transfer or returns the whale tier it reaches, 0 for none.
+    /// `wallet_state` is the source owner's, loaded when the rule set uses it.
+    pub fn evaluate(
+        &self,
+        transfer: &TransferFacts,
+        accounts: &impl RuleAccounts,
+        wallet_state: Option<&mut WalletState>,
+    ) -> Result<u8> {
+        let now = transfer.clock.unix_timestamp;
+        match self {
+            Rule::Whitelist { direction } => {
+                if direction.checks_source() {
+                    require!(accounts.is_whitelisted(&transfer.owner)?, CustomError::NotWhitelisted);
+                }
+                if direction.checks_destination() {
+                    require!(
+                        accounts.is_whitelisted(&transfer.destination_owner)?,
+                        CustomError::NotWhitelisted
+                    );
+                }
+            }
+            Rule::Denylist => {
+                require!(
+                    !accounts.is_denylisted(&transfer.owner)?
+                        && !accounts.is_denylisted(&transfer.destination_owner)?,
+                    CustomError::Denylisted
+                );
+            }
+            Rule::AllowedSenders { wallets } => {
+                require!(wallets.contains(&transfer.owner), CustomError::NotWhitelisted);
+            }
+            Rule::AllowedRecipients { wallets } => {
+                require!(
+                    wallets.contains(&transfer.destination_owner),
+                    CustomError::NotWhitelisted
+                );
+            }
+            Rule::BlockedWallets { wallets } => {
+                require!(
+                    !wallets.contains(&transfer.owner)
+                        && !wallets.contains(&transfer.destination_owner),
+                    CustomError::Denylisted
+                );
+            }
+            Rule::MaxTransferAmount { limit, mode } => {
+                require!(
+                    transfer.amount <= mode.resolve(*limit, transfer.supply),
+                    CustomError::TransferLimitExceeded
+                );
+            }
+            Rule::DecayingTransferLimit { schedule } => {
+                if let Some(limit) = schedule.limit_at(now) {
+                    require!(transfer.amount <= limit, CustomError::TransferLimitExceeded);
+                }
+            }
+            Rule::MaxWalletBalance { limit, mode } => {
+                require!(
+                    transfer.balance_cap_exempt
+                        || transfer.destination_balance <= mode.resolve(*limit, transfer.supply),
+                    CustomError::MaxWalletBalanceExceeded
+                );
+            }
+            Rule::TransferQuota { quota, window } => {
+                let state = wallet_state.ok_or(CustomError::WalletStateNotInitialized)?;
+                state.add_quota_usage(transfer.amount, *quota, *window, now)?;
+            }
+            Rule::Cooldown { interval, unit } => {
+                let state = wallet_state.ok_or(CustomError::WalletStateNotInitialized)?;
+                require!(
+                    state.elapsed_since_last_transfer(*unit, transfer.clock) >= *interval,
+                    CustomError::CooldownActive
+                );
+            }
+            Rule::Lockups => {
+                require!(
+                    transfer.source_balance >= accounts.locked_amount(now)?,
+                    CustomError::TokensLocked
+                );
+            }
+            Rule::WhaleAlert { thresholds, mode } => {
+                return Ok(whale_tier(thresholds, *mode, transfer.amount, transfer.supply));
+            }
+        }
+        Ok(0)
+    }
+}
+
+/// Highest tier of the ascending `thresholds` that `amount` reaches for a
+/// mint with `supply`, counting from 1; 0 when it is below every threshold
+pub fn whale_tier(thresholds: &[u64], mode: ThresholdMode, amount: u64, supply: u64) -> u8 {
+    thresholds
+        .iter()
+        .take_while(|&&threshold| amount >= mode.resolve(threshold, supply))
+        .count() as u8
+}
+
+/// Ordered rules of `token_mint`, created together with its `TokenInfo` and
+/// evaluated by `TransferHook` on every transfer. Sized to fit its rules
+/// exactly, so a mint only pays rent for the rules it uses. Lives at
+/// `["rules", mint]`.
+#[account]
+pub struct RuleSet {
+    pub token_mint: Pubkey,
+    pub bump: u8,
+    pub rules: Vec<Rule>,
+}
+
+impl RuleSet {
+    pub const INIT_SPACE: usize = 8 + 32 + 1 + 4;
+
+    pub fn uses_wallet_state(&self) -> bool {
+        self.rules.iter().any(Rule::uses_wallet_state)
+    }
+
+    /// Whether any `Whitelist` rule checks the source and the destination owner
+    pub fn whitelisted_sides(&self) -> (bool, bool) {
+        self.rules.iter().fold((false, false), |(source, destination), rule| match rule {
+            Rule::Whitelist { direction } => (
+                source || direction.checks_source(),
+                destination || direction.checks_destination(),
+            ),
+            _ => (source, destination),
+        })
+    }
+
+    /// First rule that `matches` picks
+    pub fn find(&self, matches: impl Fn(&Rule) -> bool) -> Option<&Rule> {
+        self.rules.iter().find(|rule| matches(rule))
+    }
+
+    /// Puts `rule` in place of the first rule `replaces` picks and drops any
+    /// other pick, or appends it when there is none
+    pub fn put(&mut self, rule: Rule, replaces: impl Fn(&Rule) -> bool) -> Result<()> {
+        match self.rules.iter().position(&replaces) {
+            Some(pos) => {
+                self.rules[pos] = rule;
+                let mut index = 0;
+                self.rules.retain(|existing| {
+                    index += 1;
+                    index - 1 == pos || !replaces(existing)
+                });
+            }
+            None => {
+                require!(self.rules.len() < MAX_RULES, CustomError::RuleSetFull);
+                self.rules.push(rule);
+            }
+        }
+        Ok(())
+    }
+
+    /// Removes every rule that `matches` picks
+    pub fn remove_where(&mut self, matches: impl Fn(&Rule) -> bool) {
+        self.rules.retain(|rule| !matches(rule));
+    }
+
+    /// Evaluates every rule in order, stopping at the first rejection, and
+    /// returns the highest whale tier any rule reports
+    pub fn evaluate(
+        &self,
+        transfer: &TransferFacts,
+        accounts: &impl RuleAccounts,
+        mut wallet_state: Option<&mut WalletState>,
+    ) -> Result<u8> {
+        let mut tier = 0;
+        for rule in &self.rules {
+            tier = tier.max(rule.evaluate(transfer, accounts, wallet_state.as_deref_mut())?);
+        }
+        Ok(tier)
+    }
+
+    /// Account size needed for the current contents, discriminator included
+    pub fn current_space(&self) -> Result<usize> {
+        Ok(8 + borsh::to_vec(self)?.len())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const TIERS: [u64; 3] = [1_000, 10_000, 100_000];
+
+    #[test]
+    fn reports_no_tier_below_the_lowest_threshold() {
+        assert_eq!(whale_tier(&TIERS, ThresholdMode::Absolute, 999, 0), 0);
+        assert_eq!(whale_tier(&[], ThresholdMode::Absolute, u64::MAX, 0), 0);
+    }
+
+    #[test]
+    fn counts_a_threshold_reached_exactly() {
+        assert_eq!(whale_tier(&TIERS, ThresholdMode::Absolute, 1_000, 0), 1);
+        assert_eq!(whale_tier(&TIERS, ThresholdMode::Absolute, 99_999, 0), 2);
+        assert_eq!(whale_tier(&TIERS, ThresholdMode::Absolute, 100_000, 0), 3);
+        assert_eq!(whale_tier(&TIERS, ThresholdMode::Absolute, u64::MAX, 0), 3);
+    }
+
+    #[test]
+    fn resolves_basis_point_tiers_against_the_supply() {
+        // 1%, 5% and 10% of a 1_000_000 supply
+        let tiers = [100, 500, 1_000];
+        let mode = ThresholdMode::SupplyBasisPoints;
+        assert_eq!(whale_tier(&tiers, mode, 9_999, 1_000_000), 0);
+        assert_eq!(whale_tier(&tiers, mode, 10_000, 1_000_000), 1);
+        assert_eq!(whale_tier(&tiers, mode, 50_000, 1_000_000), 2);
+        assert_eq!(whale_tier(&tiers, mode, 100_000, 1_000_000), 3);
+        // Doubling the supply halves the share of the same amount
+        assert_eq!(whale_tier(&tiers, mode, 100_000, 2_000_000), 2);
+    }
+}
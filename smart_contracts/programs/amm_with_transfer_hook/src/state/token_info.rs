@@ -1,6 +1,168 @@
 use anchor_lang::prelude::*;
 
+use crate::state::CustomError;
+
 pub const MAX_WHITELIST: usize = 50;
+pub const MAX_BALANCE_CAP_EXEMPTIONS: usize = 20;
+pub const MAX_LAUNCH_EXEMPTIONS: usize = 20;
+pub const MAX_PAUSE_EXEMPTIONS: usize = 10;
+pub const MAX_WHALE_TIERS: usize = 4;
+pub const BASIS_POINTS_DENOMINATOR: u64 = 10_000;
+
+/// Where whitelisted wallets are stored
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq, Debug)]
+pub enum WhitelistMode {
+    /// `TokenInfo::whitelist_addresses`, capped at `MAX_WHITELIST`
+    List,
+    /// One `WhitelistEntry` PDA per wallet, resolved through the extra account metas
+    PerWallet,
+    /// `WhitelistEntry` PDAs that wallets create themselves by proving
+    /// membership in `TokenInfo::whitelist_merkle_root`
+    Merkle,
+}
+
+/// Which side of a transfer must be whitelisted
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq, Debug)]
+pub enum WhitelistDirection {
+    /// The source owner
+    Source,
+    /// The destination token account's owner
+    Destination,
+    /// Both the source owner and the destination owner
+    Both,
+}
+
+impl WhitelistDirection {
+    pub fn checks_source(&self) -> bool {
+        matches!(self, WhitelistDirection::Source | WhitelistDirection::Both)
+    }
+
+    pub fn checks_destination(&self) -> bool {
+        matches!(self, WhitelistDirection::Destination | WhitelistDirection::Both)
+    }
+}
+
+/// How a configured amount is interpreted
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq, Debug)]
+pub enum ThresholdMode {
+    /// Raw base units
+    Absolute,
+    /// Basis points of the mint's live supply
+    SupplyBasisPoints,
+}
+
+impl ThresholdMode {
+    /// Resolves `value` to base units against the current `supply`
+    pub fn resolve(&self, value: u64, supply: u64) -> u64 {
+        match self {
+            ThresholdMode::Absolute => value,
+            ThresholdMode::SupplyBasisPoints => {
+                (supply as u128 * value as u128 / BASIS_POINTS_DENOMINATOR as u128) as u64
+            }
+        }
+    }
+
+    pub fn is_valid(&self, value: u64) -> bool {
+        match self {
+            ThresholdMode::Absolute => true,
+            ThresholdMode::SupplyBasisPoints => value <= BASIS_POINTS_DENOMINATOR,
+        }
+    }
+}
+
+/// Per-transfer limit that relaxes linearly from `start_limit` to
+/// `end_limit` over `duration` seconds from `start_time`, then disappears
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq, Debug)]
+pub struct TransferLimitSchedule {
+    pub start_limit: u64,
+    pub end_limit: u64,
+    pub start_time: i64,
+    pub duration: i64,
+}
+
+impl TransferLimitSchedule {
+    pub const SIZE: usize = 8 + 8 + 8 + 8;
+
+    pub fn is_valid(&self) -> bool {
+        self.duration > 0 && self.end_limit >= self.start_limit
+    }
+
+    /// Limit in effect at `now`, or `None` once the schedule has run out
+    pub fn limit_at(&self, now: i64) -> Option<u64> {
+        if now < self.start_time {
+            return Some(self.start_limit);
+        }
+
+        let elapsed = now.saturating_sub(self.start_time);
+        if elapsed >= self.duration {
+            return None;
+        }
+
+        let range = (self.end_limit - self.start_limit) as u128;
+        let relaxed = range * elapsed as u128 / self.duration as u128;
+        Some(self.start_limit + relaxed as u64)
+    }
+}
+
+/// Unit of the `Rule::Cooldown` interval
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq, Debug)]
+pub enum CooldownUnit {
+    Seconds,
+    Slots,
+}
+
+/// Permission checked by the settings instructions. `token_creator` itself
+/// only keeps the admin handover instructions.
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Role {
+    /// Whitelist, denylist and Merkle root
+    WhitelistManager,
+    /// Transfer limits, quotas, cooldowns, balance caps, launch schedule,
+    /// lockups and whale alerts
+    LimitsManager,
+    /// Pause, unpause and pause exemptions; stored as `pause_authority`
+    Pauser,
+    /// Assigns every role, itself included
+    RoleAdmin,
+}
+
+impl Role {
+    pub const ALL: [Role; 4] = [
+        Role::WhitelistManager,
+        Role::LimitsManager,
+        Role::Pauser,
+        Role::RoleAdmin,
+    ];
+}
+
+/// Address lists on `TokenInfo` that exempt wallets from a check
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq, Debug)]
+pub enum ExemptionList {
+    /// `balance_cap_exemptions`: token accounts or owners the balance cap skips
+    BalanceCap,
+    /// `launch_exemptions`: wallets that may send before and during the launch phase
+    Launch,
+    /// `pause_exemptions`: recovery wallets that may transfer while paused
+    Pause,
+}
+
+impl ExemptionList {
+    /// Role that manages the list
+    pub fn manager(&self) -> Role {
+        match self {
+            ExemptionList::BalanceCap | ExemptionList::Launch => Role::LimitsManager,
+            ExemptionList::Pause => Role::Pauser,
+        }
+    }
+
+    pub fn capacity(&self) -> usize {
+        match self {
+            ExemptionList::BalanceCap => MAX_BALANCE_CAP_EXEMPTIONS,
+            ExemptionList::Launch => MAX_LAUNCH_EXEMPTIONS,
+            ExemptionList::Pause => MAX_PAUSE_EXEMPTIONS,
+        }
+    }
+}
 
 #[account]
 pub struct TokenInfo {
@@ -11,15 +173,188 @@ pub struct TokenInfo {
     pub token_total_supply: u64,
     pub token_mint: Pubkey,
     pub token_creator: Pubkey,
-    pub is_whale_enabled: bool,
-    pub is_whitelist_enabled: bool,
-    pub is_total_transfer_amount_enabled: bool,
-    pub whale_amount: u64,
-    pub total_transfer_amount: u64,
+    /// Rule settings of accounts written before the `RuleSet` existed, kept
+    /// so the layout still matches; `migrate_token_info` turns them into
+    /// rules and clears them. Always false or 0 on current accounts.
+    pub legacy_whale_enabled: bool,
+    pub legacy_whitelist_enabled: bool,
+    pub legacy_transfer_limit_enabled: bool,
+    pub legacy_whale_amount: u64,
+    pub legacy_transfer_limit: u64,
     pub whitelist_addresses: Vec<Pubkey>,
     pub bump: u8,
+    pub whitelist_mode: WhitelistMode,
+    pub whitelist_merkle_root: [u8; 32],
+    /// Token accounts or wallets the balance cap does not apply to
+    pub balance_cap_exemptions: Vec<Pubkey>,
+    /// Unix timestamp before which only `launch_exemptions` may send; 0 means open
+    pub trading_opens_at: i64,
+    /// Seconds after `trading_opens_at` during which the launch limits apply
+    pub launch_phase_duration: i64,
+    /// Per-transfer cap during the launch phase; 0 disables it
+    pub launch_max_transfer_amount: u64,
+    /// Destination balance cap during the launch phase; 0 disables it
+    pub launch_max_wallet_balance: u64,
+    /// Wallets that may send before trading opens and skip launch limits
+    pub launch_exemptions: Vec<Pubkey>,
+    /// Key allowed to pause and unpause transfers, separate from `token_creator`
+    pub pause_authority: Pubkey,
+    pub is_paused: bool,
+    /// Recovery wallets that may still send or receive while paused
+    pub pause_exemptions: Vec<Pubkey>,
+    /// Proposed next `token_creator`, waiting for `accept_admin`
+    pub pending_token_creator: Option<Pubkey>,
+    pub whitelist_manager: Pubkey,
+    pub limits_manager: Pubkey,
+    pub role_admin: Pubkey,
+    /// Seconds queued changes must wait; 0 lets sensitive settings change directly
+    pub timelock_delay: i64,
+    /// Whether a `TransferStats` account exists and must be passed to the hook
+    pub has_transfer_stats: bool,
+    /// Layout version; 0 on accounts written before roles and the rule set
+    /// existed, which `migrate_token_info` upgrades
+    pub version: u8,
 }
 
 impl TokenInfo {
-    pub const INIT_SPACE: usize = 8 + 32 + 4 + 10 + 1 + 8 + 32 + 32 + 1 + 1 + 1 + 8 + 8 + 4 + (32 * MAX_WHITELIST) + 1;
+    pub const VERSION: u8 = 1;
+
+    pub const INIT_SPACE: usize = 8 + 32 + 4 + 10 + 1 + 8 + 32 + 32 + 1 + 1 + 1 + 8 + 8 + 4 + (32 * MAX_WHITELIST) + 1 + 1 + 32 + 4 + 8 + 8 + 8 + 8 + 4 + 32 + 1 + 4 + 1 + 32 + 32 + 32 + 32 + 8 + 1 + 1;
+
+    /// Whether the hook looks whitelisted wallets up as `WhitelistEntry` PDAs
+    pub fn uses_whitelist_entries(&self) -> bool {
+        matches!(self.whitelist_mode, WhitelistMode::PerWallet | WhitelistMode::Merkle)
+    }
+
+    /// Whether the destination token account or its owner is exempt from the balance cap
+    pub fn is_balance_cap_exempt(&self, token_account: &Pubkey, owner: &Pubkey) -> bool {
+        self.balance_cap_exemptions
+            .iter()
+            .any(|exempt| exempt == token_account || exempt == owner)
+    }
+
+    pub fn exemptions(&self, list: ExemptionList) -> &[Pubkey] {
+        match list {
+            ExemptionList::BalanceCap => &self.balance_cap_exemptions,
+            ExemptionList::Launch => &self.launch_exemptions,
+            ExemptionList::Pause => &self.pause_exemptions,
+        }
+    }
+
+    pub fn exemptions_mut(&mut self, list: ExemptionList) -> &mut Vec<Pubkey> {
+        match list {
+            ExemptionList::BalanceCap => &mut self.balance_cap_exemptions,
+            ExemptionList::Launch => &mut self.launch_exemptions,
+            ExemptionList::Pause => &mut self.pause_exemptions,
+        }
+    }
+
+    /// Whether `now` falls in the launch phase that follows `trading_opens_at`
+    pub fn is_in_launch_phase(&self, now: i64) -> bool {
+        now < self.trading_opens_at.saturating_add(self.launch_phase_duration)
+    }
+
+    pub fn role_holder(&self, role: Role) -> Pubkey {
+        match role {
+            Role::WhitelistManager => self.whitelist_manager,
+            Role::LimitsManager => self.limits_manager,
+            Role::Pauser => self.pause_authority,
+            Role::RoleAdmin => self.role_admin,
+        }
+    }
+
+    pub fn set_role_holder(&mut self, role: Role, holder: Pubkey) {
+        match role {
+            Role::WhitelistManager => self.whitelist_manager = holder,
+            Role::LimitsManager => self.limits_manager = holder,
+            Role::Pauser => self.pause_authority = holder,
+            Role::RoleAdmin => self.role_admin = holder,
+        }
+    }
+
+    pub fn require_role(&self, role: Role, signer: &Pubkey) -> Result<()> {
+        require_keys_eq!(self.role_holder(role), *signer, CustomError::MissingRole);
+        Ok(())
+    }
+
+    /// Whether `key` is the admin or holds a role that changes rule accounts
+    pub fn can_update_meta_list(&self, key: &Pubkey) -> bool {
+        [self.token_creator, self.whitelist_manager, self.limits_manager].contains(key)
+    }
+
+    /// Whether sensitive settings must go through the change queue
+    pub fn is_timelocked(&self) -> bool {
+        self.timelock_delay > 0
+    }
+
+    /// Account size needed for the current contents, discriminator included.
+    /// An unset `pending_token_creator` is counted as set, so `propose_admin`
+    /// can fill it in without a realloc.
+    pub fn current_space(&self) -> Result<usize> {
+        let unset = if self.pending_token_creator.is_none() { 32 } else { 0 };
+        Ok(8 + borsh::to_vec(self)?.len() + unset)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const START: i64 = 1_700_000_000;
+    const DURATION: i64 = 7 * 24 * 60 * 60;
+
+    fn schedule() -> TransferLimitSchedule {
+        TransferLimitSchedule {
+            start_limit: 1_000,
+            end_limit: 8_000,
+            start_time: START,
+            duration: DURATION,
+        }
+    }
+
+    #[test]
+    fn holds_the_start_limit_until_the_schedule_starts() {
+        assert_eq!(schedule().limit_at(START - 1), Some(1_000));
+        assert_eq!(schedule().limit_at(START), Some(1_000));
+    }
+
+    #[test]
+    fn relaxes_linearly_and_runs_out_at_the_end() {
+        let schedule = schedule();
+        assert_eq!(schedule.limit_at(START + DURATION / 2), Some(4_500));
+        assert_eq!(schedule.limit_at(START + DURATION - 1), Some(7_999));
+
+        assert_eq!(schedule.limit_at(START + DURATION), None);
+        assert_eq!(schedule.limit_at(i64::MAX), None);
+    }
+
+    #[test]
+    fn does_not_overflow_for_a_schedule_starting_at_i64_min() {
+        let schedule = TransferLimitSchedule {
+            start_time: i64::MIN,
+            ..schedule()
+        };
+        assert_eq!(schedule.limit_at(START), None);
+    }
+
+    #[test]
+    fn resolves_basis_points_against_the_live_supply() {
+        assert_eq!(ThresholdMode::Absolute.resolve(250, 1_000_000), 250);
+        assert_eq!(ThresholdMode::SupplyBasisPoints.resolve(250, 1_000_000), 25_000);
+        assert_eq!(
+            ThresholdMode::SupplyBasisPoints.resolve(BASIS_POINTS_DENOMINATOR, 1_000_000),
+            1_000_000
+        );
+        // Rounds down, and a zero supply resolves every threshold to zero
+        assert_eq!(ThresholdMode::SupplyBasisPoints.resolve(1, 9_999), 0);
+        assert_eq!(ThresholdMode::SupplyBasisPoints.resolve(250, 0), 0);
+    }
+
+    #[test]
+    fn does_not_overflow_for_the_largest_supply() {
+        assert_eq!(
+            ThresholdMode::SupplyBasisPoints.resolve(BASIS_POINTS_DENOMINATOR, u64::MAX),
+            u64::MAX
+        );
+    }
 }
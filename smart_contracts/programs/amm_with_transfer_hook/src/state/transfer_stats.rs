@@ -0,0 +1,29 @@
+use anchor_lang::prelude::*;
+
+/// Running totals of the transfers `TransferHook` let through for
+/// `token_mint`. Rejected transfers revert every account write, so they are
+/// not counted here and have to be read from failed transaction logs. Lives
+/// at `["stats", mint]`.
+#[account]
+pub struct TransferStats {
+    pub token_mint: Pubkey,
+    pub bump: u8,
+    pub transfer_count: u64,
+    pub total_volume: u128,
+    pub largest_transfer: u64,
+    /// Transfers that emitted at least one `WhaleTransferEvent`
+    pub whale_alert_count: u64,
+}
+
+impl TransferStats {
+    pub const INIT_SPACE: usize = 8 + 32 + 1 + 8 + 16 + 8 + 8;
+
+    pub fn record(&mut self, amount: u64, whale_alert: bool) {
+        self.transfer_count = self.transfer_count.saturating_add(1);
+        self.total_volume = self.total_volume.saturating_add(amount as u128);
+        self.largest_transfer = self.largest_transfer.max(amount);
+        if whale_alert {
+            self.whale_alert_count = self.whale_alert_count.saturating_add(1);
+        }
+    }
+}
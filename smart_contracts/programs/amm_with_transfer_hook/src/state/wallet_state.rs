@@ -0,0 +1,153 @@
+use anchor_lang::prelude::*;
+
+use crate::state::{CooldownUnit, CustomError};
+
+/// Time buckets the rolling quota window is split into
+pub const QUOTA_BUCKETS: usize = 8;
+
+/// Longest rolling quota window, in seconds (one year)
+pub const MAX_QUOTA_WINDOW: i64 = 366 * 24 * 60 * 60;
+
+/// Per-wallet transfer bookkeeping that `TransferHook` updates on every
+/// outbound transfer of `token_mint`. Lives at `["wallet-state", mint, wallet]`.
+#[account]
+pub struct WalletState {
+    pub token_mint: Pubkey,
+    pub wallet: Pubkey,
+    /// Amount sent per bucket of `quota_bucket_width` seconds, indexed by
+    /// bucket number modulo `QUOTA_BUCKETS`
+    pub quota_buckets: [u64; QUOTA_BUCKETS],
+    /// Bucket number of the latest quota update
+    pub quota_last_bucket: i64,
+    /// Bucket width the buckets were filled with; 0 until the first update
+    pub quota_bucket_width: i64,
+    pub bump: u8,
+    /// Unix timestamp and slot of the last outbound transfer, for cooldowns
+    pub last_transfer_at: i64,
+    pub last_transfer_slot: u64,
+}
+
+impl WalletState {
+    pub const INIT_SPACE: usize = 8 + 32 + 32 + (8 * QUOTA_BUCKETS) + 8 + 8 + 1 + 8 + 8;
+
+    /// Seconds or slots since the last outbound transfer
+    pub fn elapsed_since_last_transfer(&self, unit: CooldownUnit, clock: &Clock) -> u64 {
+        match unit {
+            CooldownUnit::Seconds => clock.unix_timestamp.saturating_sub(self.last_transfer_at).max(0) as u64,
+            CooldownUnit::Slots => clock.slot.saturating_sub(self.last_transfer_slot),
+        }
+    }
+
+    /// Records `amount` sent at `now` and rejects it if the total sent over
+    /// the last `window` seconds would exceed `quota`. The buckets together
+    /// span at least one full window, so a transfer keeps counting until
+    /// `window` seconds have passed, and at most one bucket longer.
+    pub fn add_quota_usage(&mut self, amount: u64, quota: u64, window: i64, now: i64) -> Result<()> {
+        let spans = QUOTA_BUCKETS as i64 - 1;
+        let width = (window / spans + i64::from(window % spans != 0)).max(1);
+        let bucket = now.div_euclid(width);
+
+        if width != self.quota_bucket_width {
+            // The window changed, so bucket numbers changed meaning; keep
+            // the earlier usage in the current bucket rather than drop it
+            let used = self.quota_used();
+            self.quota_buckets = [0; QUOTA_BUCKETS];
+            self.quota_buckets[Self::quota_index(bucket)] = used;
+            self.quota_bucket_width = width;
+            self.quota_last_bucket = bucket;
+        }
+
+        // Clear the buckets that fell out of the window since the last update
+        let expired = bucket
+            .saturating_sub(self.quota_last_bucket)
+            .clamp(0, QUOTA_BUCKETS as i64);
+        for offset in 0..expired {
+            self.quota_buckets[Self::quota_index(bucket - offset)] = 0;
+        }
+        self.quota_last_bucket = self.quota_last_bucket.max(bucket);
+
+        let transferred = self
+            .quota_used()
+            .checked_add(amount)
+            .ok_or(CustomError::TransferQuotaExceeded)?;
+        require!(transferred <= quota, CustomError::TransferQuotaExceeded);
+
+        self.quota_buckets[Self::quota_index(bucket)] += amount;
+        Ok(())
+    }
+
+    fn quota_used(&self) -> u64 {
+        self.quota_buckets
+            .iter()
+            .fold(0u64, |used, amount| used.saturating_add(*amount))
+    }
+
+    fn quota_index(bucket: i64) -> usize {
+        bucket.rem_euclid(QUOTA_BUCKETS as i64) as usize
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const QUOTA: u64 = 1_000;
+    const WINDOW: i64 = 24 * 60 * 60;
+
+    fn wallet_state() -> WalletState {
+        WalletState {
+            token_mint: Pubkey::default(),
+            wallet: Pubkey::default(),
+            quota_buckets: [0; QUOTA_BUCKETS],
+            quota_last_bucket: 0,
+            quota_bucket_width: 0,
+            bump: 0,
+            last_transfer_at: 0,
+            last_transfer_slot: 0,
+        }
+    }
+
+    #[test]
+    fn rejects_a_second_quota_right_after_the_window_boundary() {
+        let mut state = wallet_state();
+        let start = 1_700_000_000;
+
+        state.add_quota_usage(1, QUOTA, WINDOW, start).unwrap();
+        state.add_quota_usage(QUOTA - 1, QUOTA, WINDOW, start + WINDOW - 1).unwrap();
+
+        // A fixed window opened by the first transfer would have reset here
+        assert!(state.add_quota_usage(QUOTA, QUOTA, WINDOW, start + WINDOW + 1).is_err());
+
+        let width = state.quota_bucket_width;
+        state.add_quota_usage(QUOTA, QUOTA, WINDOW, start + 2 * WINDOW - 1 + width).unwrap();
+    }
+
+    #[test]
+    fn frees_usage_once_a_full_window_has_passed() {
+        let mut state = wallet_state();
+        let start = 1_700_000_000;
+        state.add_quota_usage(QUOTA, QUOTA, WINDOW, start).unwrap();
+        let width = state.quota_bucket_width;
+        assert!(state.add_quota_usage(1, QUOTA, WINDOW, start + WINDOW - 1).is_err());
+
+        state.add_quota_usage(QUOTA, QUOTA, WINDOW, start + WINDOW + width).unwrap();
+    }
+
+    #[test]
+    fn handles_windows_near_i64_max() {
+        let mut state = wallet_state();
+        let start = 1_700_000_000;
+
+        state.add_quota_usage(QUOTA, QUOTA, i64::MAX, start).unwrap();
+        assert!(state.add_quota_usage(1, QUOTA, i64::MAX, start + WINDOW).is_err());
+    }
+
+    #[test]
+    fn keeps_usage_when_the_window_changes() {
+        let mut state = wallet_state();
+        let start = 1_700_000_000;
+
+        state.add_quota_usage(QUOTA, QUOTA, WINDOW, start).unwrap();
+        assert!(state.add_quota_usage(1, QUOTA, WINDOW / 2, start + 60).is_err());
+    }
+}
@@ -0,0 +1,14 @@
+use anchor_lang::prelude::*;
+
+/// Marks `wallet` as whitelisted for `token_mint` when the token uses
+/// `WhitelistMode::PerWallet`. Lives at `["whitelist", mint, wallet]`.
+#[account]
+pub struct WhitelistEntry {
+    pub token_mint: Pubkey,
+    pub wallet: Pubkey,
+    pub bump: u8,
+}
+
+impl WhitelistEntry {
+    pub const INIT_SPACE: usize = 8 + 32 + 32 + 1;
+}
@@ -0,0 +1,156 @@
+use anchor_lang::{prelude::*, solana_program::hash::hashv, system_program};
+
+use crate::state::{CustomError, RuleSet, TokenInfo};
+
+/// Resizes a program-owned account, topping up rent from `payer` when it
+/// grows and refunding the surplus back to `payer` when it shrinks.
+pub fn resize_account<'info>(
+    account: &AccountInfo<'info>,
+    payer: &AccountInfo<'info>,
+    system_program: &AccountInfo<'info>,
+    new_size: usize,
+) -> Result<()> {
+    let current_size = account.data_len();
+    if new_size == current_size {
+        return Ok(());
+    }
+
+    let rent = Rent::get()?;
+    let lamports_required = rent.minimum_balance(new_size);
+
+    if new_size > current_size {
+        let rent_diff = lamports_required.saturating_sub(account.lamports());
+        if rent_diff > 0 {
+            let cpi_accounts = system_program::Transfer {
+                from: payer.clone(),
+                to: account.clone(),
+            };
+            let cpi_ctx = CpiContext::new(system_program.clone(), cpi_accounts);
+            system_program::transfer(cpi_ctx, rent_diff)?;
+        }
+        account.resize(new_size)?;
+    } else {
+        account.resize(new_size)?;
+        let refund = account.lamports().saturating_sub(lamports_required);
+        if refund > 0 {
+            **payer.try_borrow_mut_lamports()? += refund;
+            **account.try_borrow_mut_lamports()? -= refund;
+        }
+    }
+
+    Ok(())
+}
+
+/// Resizes `token_info` to exactly fit its contents, settling the rent
+/// difference with `payer`.
+pub fn resize_token_info<'info>(
+    token_info: &Account<'info, TokenInfo>,
+    payer: &AccountInfo<'info>,
+    system_program: &AccountInfo<'info>,
+) -> Result<()> {
+    let new_size = token_info.current_space()?;
+    resize_account(&token_info.to_account_info(), payer, system_program, new_size)?;
+    msg!("TokenInfo resized to {} bytes", new_size);
+    Ok(())
+}
+
+/// Resizes `rule_set` to exactly fit its rules, settling the rent difference
+/// with `payer`.
+pub fn resize_rule_set<'info>(
+    rule_set: &Account<'info, RuleSet>,
+    payer: &AccountInfo<'info>,
+    system_program: &AccountInfo<'info>,
+) -> Result<()> {
+    let new_size = rule_set.current_space()?;
+    resize_account(&rule_set.to_account_info(), payer, system_program, new_size)
+}
+
+/// Adds `address` to `list` unless it is already there, failing with `full`
+/// once `list` holds `capacity` addresses. Returns whether it was added.
+pub fn insert_capped(
+    list: &mut Vec<Pubkey>,
+    address: Pubkey,
+    capacity: usize,
+    full: CustomError,
+) -> Result<bool> {
+    if list.contains(&address) {
+        return Ok(false);
+    }
+    if list.len() >= capacity {
+        return Err(full.into());
+    }
+    list.push(address);
+    Ok(true)
+}
+
+/// Removes `address` from `list`; returns whether it was there
+pub fn remove_address(list: &mut Vec<Pubkey>, address: &Pubkey) -> bool {
+    match list.iter().position(|x| x == address) {
+        Some(pos) => {
+            list.remove(pos);
+            true
+        }
+        None => false,
+    }
+}
+
+/// Verifies `leaf` against `root` using sorted-pair SHA-256 hashing, so
+/// proofs carry no left/right flags.
+pub fn verify_merkle_proof(proof: &[[u8; 32]], root: &[u8; 32], leaf: [u8; 32]) -> bool {
+    let computed = proof.iter().fold(leaf, |node, sibling| {
+        if node <= *sibling {
+            hashv(&[&node, sibling]).to_bytes()
+        } else {
+            hashv(&[sibling, &node]).to_bytes()
+        }
+    });
+    computed == *root
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn leaf(byte: u8) -> [u8; 32] {
+        hashv(&[&[byte; 32]]).to_bytes()
+    }
+
+    fn hash_pair(a: [u8; 32], b: [u8; 32]) -> [u8; 32] {
+        if a <= b {
+            hashv(&[&a, &b]).to_bytes()
+        } else {
+            hashv(&[&b, &a]).to_bytes()
+        }
+    }
+
+    #[test]
+    fn accepts_a_proof_for_every_leaf() {
+        let leaves = [leaf(1), leaf(2), leaf(3), leaf(4)];
+        let left = hash_pair(leaves[0], leaves[1]);
+        let right = hash_pair(leaves[2], leaves[3]);
+        let root = hash_pair(left, right);
+
+        assert!(verify_merkle_proof(&[leaves[1], right], &root, leaves[0]));
+        assert!(verify_merkle_proof(&[leaves[0], right], &root, leaves[1]));
+        assert!(verify_merkle_proof(&[leaves[3], left], &root, leaves[2]));
+        assert!(verify_merkle_proof(&[leaves[2], left], &root, leaves[3]));
+    }
+
+    #[test]
+    fn rejects_an_invalid_proof() {
+        let leaves = [leaf(1), leaf(2), leaf(3), leaf(4)];
+        let left = hash_pair(leaves[0], leaves[1]);
+        let right = hash_pair(leaves[2], leaves[3]);
+        let root = hash_pair(left, right);
+
+        // Leaf that is not in the tree
+        assert!(!verify_merkle_proof(&[leaves[1], right], &root, leaf(5)));
+        // Wrong sibling
+        assert!(!verify_merkle_proof(&[leaves[2], right], &root, leaves[0]));
+        // Truncated proof
+        assert!(!verify_merkle_proof(&[leaves[1]], &root, leaves[0]));
+        // An empty proof only verifies the root itself
+        assert!(!verify_merkle_proof(&[], &root, leaves[0]));
+        assert!(verify_merkle_proof(&[], &root, root));
+    }
+}
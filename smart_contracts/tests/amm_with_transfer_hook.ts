@@ -10,9 +10,28 @@ import {
   getMintLen,
   createInitializeMintInstruction,
   createInitializeTransferHookInstruction,
+  createAssociatedTokenAccountIdempotentInstruction,
+  createMintToInstruction,
+  createTransferCheckedWithTransferHookInstruction,
 } from "@solana/spl-token";
 import { Keypair, PublicKey, sendAndConfirmTransaction, SystemProgram, Transaction } from '@solana/web3.js';
 import { assert } from "chai";
+import { createHash } from "crypto";
+
+const sha256 = (...parts: Buffer[]) => createHash("sha256").update(Buffer.concat(parts)).digest();
+
+// Sorted-pair hashing, matching `verify_merkle_proof` on-chain
+const hashPair = (a: Buffer, b: Buffer) => (Buffer.compare(a, b) <= 0 ? sha256(a, b) : sha256(b, a));
+
+// Decodes the Anchor events logged by a confirmed transaction
+const parseEvents = async (program: Program<AmmWithTransferHook>, signature: string) => {
+  const tx = await program.provider.connection.getTransaction(signature, {
+    commitment: "confirmed",
+    maxSupportedTransactionVersion: 0,
+  });
+  const parser = new anchor.EventParser(program.programId, program.coder);
+  return [...parser.parseLogs(tx.meta.logMessages)];
+};
 
 describe("amm_with_transfer_hook", () => {
   // Configure the client to use the local cluster.
@@ -56,6 +75,17 @@ describe("amm_with_transfer_hook", () => {
     [Buffer.from('token-info'), mint2022.publicKey.toBuffer()],
     program.programId,
   )[0];
+  const ruleSetPda = anchor.web3.PublicKey.findProgramAddressSync(
+    [Buffer.from('rules'), mint2022.publicKey.toBuffer()],
+    program.programId,
+  )[0];
+
+  // Fields of the first rule of `kind` (e.g. "whaleAlert") in the mint's rule set
+  const findRule = async (kind: string) => {
+    const ruleSet = await program.account.ruleSet.fetch(ruleSetPda);
+    const rule: any = ruleSet.rules.find((rule) => kind in rule);
+    return rule?.[kind];
+  };
 
   it("Create a new token mint", async() => {
     mint = await createMint(
@@ -119,6 +149,7 @@ describe("amm_with_transfer_hook", () => {
       .accounts({
         mint: mint2022.publicKey,
         payer: wallet.publicKey,
+        authority: wallet.publicKey,
       })
       .signers([wallet.payer])
       .rpc();
@@ -126,6 +157,117 @@ describe("amm_with_transfer_hook", () => {
     console.log("Initialized token info:", tx);
   });
 
+  it("Rejects re-initialization of token info", async () => {
+    try {
+      await program.methods
+        .initializeTokenInfo(
+          "Hijacked", // token_name
+          "HIJACK", // token_symbol
+          new anchor.BN(1000000), // token_total_supply
+          false, // is_whale_enabled
+          false, // is_whitelist_enabled
+          false, // is_total_transfer_amount_enabled
+          new anchor.BN(0), // whale_amount
+          new anchor.BN(0) // total_transfer_amount
+        )
+        .accounts({
+          mint: mint2022.publicKey,
+          payer: wallet.publicKey,
+          authority: wallet.publicKey,
+        })
+        .signers([wallet.payer])
+        .rpc();
+      assert.fail("Expected error for re-initialization");
+    } catch (err) {
+      // `init` refuses accounts that already exist
+      assert.include(err.toString(), "already in use");
+    }
+  });
+
+  it("Rejects initialization by a key that is not a mint authority", async () => {
+    const otherMint = Keypair.generate();
+    const intruder = Keypair.generate();
+    const mintLen = getMintLen([ExtensionType.TransferHook]);
+    const lamports = await provider.connection.getMinimumBalanceForRentExemption(mintLen);
+    await sendAndConfirmTransaction(
+      provider.connection,
+      new Transaction().add(
+        SystemProgram.createAccount({
+          fromPubkey: wallet.publicKey,
+          newAccountPubkey: otherMint.publicKey,
+          space: mintLen,
+          lamports,
+          programId: TOKEN_2022_PROGRAM_ID,
+        }),
+        createInitializeTransferHookInstruction(
+          otherMint.publicKey,
+          wallet.publicKey,
+          program.programId,
+          TOKEN_2022_PROGRAM_ID,
+        ),
+        createInitializeMintInstruction(otherMint.publicKey, 9, wallet.publicKey, null, TOKEN_2022_PROGRAM_ID),
+      ),
+      [wallet.payer, otherMint],
+    );
+
+    try {
+      await program.methods
+        .initializeTokenInfo(
+          "Hijacked", // token_name
+          "HIJACK", // token_symbol
+          new anchor.BN(1000000), // token_total_supply
+          false, // is_whale_enabled
+          false, // is_whitelist_enabled
+          false, // is_total_transfer_amount_enabled
+          new anchor.BN(0), // whale_amount
+          new anchor.BN(0) // total_transfer_amount
+        )
+        .accounts({
+          mint: otherMint.publicKey,
+          payer: wallet.publicKey,
+          authority: intruder.publicKey,
+        })
+        .signers([wallet.payer, intruder])
+        .rpc();
+      assert.fail("Expected a non-authority initializer to be rejected");
+    } catch (err) {
+      assert.include(err.toString(), "UnauthorizedInitializer");
+    }
+  });
+
+  it("Rewrites the extra account meta list", async () => {
+    const tx = await program.methods
+      .updateExtraAccountMetaList()
+      .accounts({
+        mint: mint2022.publicKey,
+        authority: wallet.publicKey,
+      })
+      .signers([wallet.payer])
+      .rpc();
+
+    console.log("Extra account meta list updated:", tx);
+  });
+
+  it("Refuses to migrate a TokenInfo already on the current layout", async () => {
+    const tokenInfo = await program.account.tokenInfo.fetch(tokenInfoPda);
+    assert.equal(tokenInfo.version, 1);
+    assert.ok(tokenInfo.whitelistManager.equals(wallet.publicKey));
+
+    try {
+      await program.methods
+        .migrateTokenInfo()
+        .accounts({
+          mint: mint2022.publicKey,
+          tokenCreator: wallet.publicKey,
+        })
+        .signers([wallet.payer])
+        .rpc();
+      assert.fail("Expected a current TokenInfo to be rejected");
+    } catch (err) {
+      assert.include(err.toString(), "AlreadyMigrated");
+    }
+  });
+
   it("Adds address to whitelist", async () => {
     const tx = await program.methods
       .addToWhitelist(recipient.publicKey)
@@ -138,47 +280,1184 @@ describe("amm_with_transfer_hook", () => {
     console.log("Added to whitelist:", tx);
   });
 
-  it("Updates whale alert settings", async () => {
+  it("Removes address from whitelist", async () => {
+    const removed = Keypair.generate().publicKey;
+
+    await program.methods
+      .addToWhitelist(removed)
+      .accounts({
+        tokenInfo: tokenInfoPda,
+      })
+      .signers([wallet.payer])
+      .rpc();
+
     const tx = await program.methods
-      .updateWhaleAlert(true, new anchor.BN(5000))
+      .removeFromWhitelist(removed)
       .accounts({
         tokenInfo: tokenInfoPda,
       })
       .signers([wallet.payer])
       .rpc();
 
-    console.log("Whale alert updated:", tx);
+    const tokenInfo = await program.account.tokenInfo.fetch(tokenInfoPda);
+    assert.isFalse(tokenInfo.whitelistAddresses.some((address) => address.equals(removed)));
+
+    console.log("Removed from whitelist:", tx);
   });
 
-  it("Updates total transfer limit", async () => {
-    const tx = await program.methods
-      .setMaxTransferLimit(new anchor.BN(250_000))
+  it("Adds and removes a batch of whitelist addresses", async () => {
+    const batch = [Keypair.generate().publicKey, Keypair.generate().publicKey];
+
+    const addTx = await program.methods
+      .addManyToWhitelist([...batch, batch[0]])
       .accounts({
         tokenInfo: tokenInfoPda,
       })
       .signers([wallet.payer])
       .rpc();
 
-    console.log("Max transfer limit updated:", tx);
+    let tokenInfo = await program.account.tokenInfo.fetch(tokenInfoPda);
+    assert.equal(
+      tokenInfo.whitelistAddresses.filter((address) => address.equals(batch[0])).length,
+      1
+    );
+
+    const removeTx = await program.methods
+      .removeManyFromWhitelist(batch)
+      .accounts({
+        tokenInfo: tokenInfoPda,
+      })
+      .signers([wallet.payer])
+      .rpc();
+
+    tokenInfo = await program.account.tokenInfo.fetch(tokenInfoPda);
+    assert.isFalse(tokenInfo.whitelistAddresses.some((address) => batch.some((b) => b.equals(address))));
+
+    console.log("Batch whitelist updated:", addTx, removeTx);
   });
 
-  it("Fails transfer if not whitelisted (simulate)", async () => {
-    const nonWhitelisted = Keypair.generate();
+  it("Manages per-wallet whitelist entries", async () => {
+    const [whitelistEntryPda] = anchor.web3.PublicKey.findProgramAddressSync(
+      [Buffer.from('whitelist'), mint2022.publicKey.toBuffer(), recipient.publicKey.toBuffer()],
+      program.programId,
+    );
 
-    try {
-      await program.methods
-        .transferHook(new anchor.BN(1000))
-        .accounts({
-          sourceToken: sourceTokenAccount,
-          destinationToken: destinationTokenAccount,
-          owner: nonWhitelisted.publicKey,
-          mint: mint2022.publicKey,
+    await program.methods
+      .setWhitelistMode({ perWallet: {} })
+      .accounts({
+        tokenInfo: tokenInfoPda,
+        authority: wallet.publicKey,
+      })
+      .signers([wallet.payer])
+      .rpc();
+
+    await program.methods
+      .addWhitelistEntry(recipient.publicKey)
+      .accounts({
+        tokenInfo: tokenInfoPda,
+      })
+      .signers([wallet.payer])
+      .rpc();
+
+    const entry = await program.account.whitelistEntry.fetch(whitelistEntryPda);
+    assert.isTrue(entry.wallet.equals(recipient.publicKey));
+
+    await program.methods
+      .removeWhitelistEntry()
+      .accounts({
+        tokenInfo: tokenInfoPda,
+        whitelistEntry: whitelistEntryPda,
+      })
+      .signers([wallet.payer])
+      .rpc();
+
+    assert.isNull(await provider.connection.getAccountInfo(whitelistEntryPda));
+
+    // Back to the embedded list for the remaining tests
+    await program.methods
+      .setWhitelistMode({ list: {} })
+      .accounts({
+        tokenInfo: tokenInfoPda,
+        authority: wallet.publicKey,
+      })
+      .signers([wallet.payer])
+      .rpc();
+  });
+
+  it("Registers whitelist membership with a Merkle proof", async () => {
+    const member = Keypair.generate();
+    const otherLeaf = sha256(Keypair.generate().publicKey.toBuffer());
+    const memberLeaf = sha256(member.publicKey.toBuffer());
+    const root = hashPair(memberLeaf, otherLeaf);
+
+    await sendAndConfirmTransaction(
+      provider.connection,
+      new Transaction().add(
+        SystemProgram.transfer({
+          fromPubkey: wallet.publicKey,
+          toPubkey: member.publicKey,
+          lamports: anchor.web3.LAMPORTS_PER_SOL / 10,
         })
-        .signers([wallet.payer])
-        .rpc();
-      assert.fail("Expected error for non-whitelisted transfer");
-    } catch (err) {
-      console.log("Correctly failed transfer for non-whitelisted account");
-    }
+      ),
+      [wallet.payer]
+    );
+
+    await program.methods
+      .setWhitelistMode({ merkle: {} })
+      .accounts({
+        tokenInfo: tokenInfoPda,
+        authority: wallet.publicKey,
+      })
+      .signers([wallet.payer])
+      .rpc();
+
+    await program.methods
+      .setWhitelistMerkleRoot([...root])
+      .accounts({
+        tokenInfo: tokenInfoPda,
+        authority: wallet.publicKey,
+      })
+      .signers([wallet.payer])
+      .rpc();
+
+    const tx = await program.methods
+      .registerWhitelistMembership([[...otherLeaf]])
+      .accounts({
+        tokenInfo: tokenInfoPda,
+        wallet: member.publicKey,
+      })
+      .signers([member])
+      .rpc();
+
+    const [whitelistEntryPda] = anchor.web3.PublicKey.findProgramAddressSync(
+      [Buffer.from('whitelist'), mint2022.publicKey.toBuffer(), member.publicKey.toBuffer()],
+      program.programId,
+    );
+    const entry = await program.account.whitelistEntry.fetch(whitelistEntryPda);
+    assert.isTrue(entry.wallet.equals(member.publicKey));
+
+    await program.methods
+      .setWhitelistMode({ list: {} })
+      .accounts({
+        tokenInfo: tokenInfoPda,
+        authority: wallet.publicKey,
+      })
+      .signers([wallet.payer])
+      .rpc();
+
+    console.log("Registered whitelist membership:", tx);
+  });
+
+  it("Manages the denylist", async () => {
+    const blocked = Keypair.generate().publicKey;
+    const [denylistEntryPda] = anchor.web3.PublicKey.findProgramAddressSync(
+      [Buffer.from('denylist'), mint2022.publicKey.toBuffer(), blocked.toBuffer()],
+      program.programId,
+    );
+
+    await program.methods
+      .updateDenylist(true)
+      .accounts({
+        tokenInfo: tokenInfoPda,
+        authority: wallet.publicKey,
+      })
+      .signers([wallet.payer])
+      .rpc();
+
+    await program.methods
+      .addToDenylist(blocked)
+      .accounts({
+        tokenInfo: tokenInfoPda,
+        changeQueue: null,
+      })
+      .signers([wallet.payer])
+      .rpc();
+
+    const entry = await program.account.denylistEntry.fetch(denylistEntryPda);
+    assert.isTrue(entry.wallet.equals(blocked));
+
+    await program.methods
+      .removeFromDenylist()
+      .accounts({
+        tokenInfo: tokenInfoPda,
+        denylistEntry: denylistEntryPda,
+      })
+      .signers([wallet.payer])
+      .rpc();
+
+    await program.methods
+      .updateDenylist(false)
+      .accounts({
+        tokenInfo: tokenInfoPda,
+        authority: wallet.publicKey,
+      })
+      .signers([wallet.payer])
+      .rpc();
+  });
+
+  it("Sets the whitelist direction", async () => {
+    await program.methods
+      .setWhitelistDirection({ both: {} })
+      .accounts({
+        tokenInfo: tokenInfoPda,
+        authority: wallet.publicKey,
+      })
+      .signers([wallet.payer])
+      .rpc();
+
+    const whitelist = await findRule("whitelist");
+    assert.deepEqual(whitelist.direction, { both: {} });
+
+    await program.methods
+      .setWhitelistDirection({ source: {} })
+      .accounts({
+        tokenInfo: tokenInfoPda,
+        authority: wallet.publicKey,
+      })
+      .signers([wallet.payer])
+      .rpc();
+  });
+
+  it("Configures a per-wallet transfer quota", async () => {
+    const [walletStatePda] = anchor.web3.PublicKey.findProgramAddressSync(
+      [Buffer.from('wallet-state'), mint2022.publicKey.toBuffer(), wallet.publicKey.toBuffer()],
+      program.programId,
+    );
+
+    await program.methods
+      .initializeWalletState()
+      .accounts({
+        payer: wallet.publicKey,
+        wallet: wallet.publicKey,
+        tokenInfo: tokenInfoPda,
+      })
+      .signers([wallet.payer])
+      .rpc();
+
+    const walletState = await program.account.walletState.fetch(walletStatePda);
+    assert.isTrue(walletState.wallet.equals(wallet.publicKey));
+
+    await program.methods
+      .setTransferQuota(true, new anchor.BN(100_000), new anchor.BN(24 * 60 * 60))
+      .accounts({
+        tokenInfo: tokenInfoPda,
+        authority: wallet.publicKey,
+      })
+      .signers([wallet.payer])
+      .rpc();
+
+    const quota = await findRule("transferQuota");
+    assert.equal(quota.quota.toNumber(), 100_000);
+
+    await program.methods
+      .setTransferQuota(false, new anchor.BN(0), new anchor.BN(0))
+      .accounts({
+        tokenInfo: tokenInfoPda,
+        authority: wallet.publicKey,
+      })
+      .signers([wallet.payer])
+      .rpc();
+  });
+
+  it("Configures a per-wallet transfer cooldown", async () => {
+    await program.methods
+      .setTransferCooldown(true, new anchor.BN(5), { slots: {} })
+      .accounts({
+        tokenInfo: tokenInfoPda,
+        authority: wallet.publicKey,
+      })
+      .signers([wallet.payer])
+      .rpc();
+
+    const cooldown = await findRule("cooldown");
+    assert.equal(cooldown.interval.toNumber(), 5);
+    assert.deepEqual(cooldown.unit, { slots: {} });
+
+    await program.methods
+      .setTransferCooldown(false, new anchor.BN(0), { seconds: {} })
+      .accounts({
+        tokenInfo: tokenInfoPda,
+        authority: wallet.publicKey,
+      })
+      .signers([wallet.payer])
+      .rpc();
+  });
+
+  it("Configures the max wallet balance with an exempt vault", async () => {
+    const vault = Keypair.generate().publicKey;
+
+    await program.methods
+      .setMaxWalletBalance(true, new anchor.BN(200), { supplyBasisPoints: {} })
+      .accounts({
+        tokenInfo: tokenInfoPda,
+        authority: wallet.publicKey,
+      })
+      .signers([wallet.payer])
+      .rpc();
+
+    await program.methods
+      .addBalanceCapExemption(vault)
+      .accounts({
+        tokenInfo: tokenInfoPda,
+        authority: wallet.publicKey,
+      })
+      .signers([wallet.payer])
+      .rpc();
+
+    let tokenInfo = await program.account.tokenInfo.fetch(tokenInfoPda);
+    assert.isTrue(tokenInfo.balanceCapExemptions.some((address) => address.equals(vault)));
+
+    const removeTx = await program.methods
+      .removeBalanceCapExemption(vault)
+      .accounts({
+        tokenInfo: tokenInfoPda,
+        authority: wallet.publicKey,
+      })
+      .signers([wallet.payer])
+      .rpc({ commitment: "confirmed" });
+
+    const removed = (await parseEvents(program, removeTx)).find((e) => /^exemptionUpdatedEvent$/i.test(e.name));
+    assert.isDefined(removed);
+    assert.property(removed.data.list, "balanceCap");
+    assert.isTrue(removed.data.address.equals(vault));
+    assert.isTrue(removed.data.wasExempt);
+    assert.isFalse(removed.data.isExempt);
+
+    await program.methods
+      .setMaxWalletBalance(false, new anchor.BN(0), { absolute: {} })
+      .accounts({
+        tokenInfo: tokenInfoPda,
+        authority: wallet.publicKey,
+      })
+      .signers([wallet.payer])
+      .rpc();
+
+    assert.isUndefined(await findRule("maxWalletBalance"));
+  });
+
+  it("Configures the launch schedule", async () => {
+    const now = Math.floor(Date.now() / 1000);
+
+    await program.methods
+      .addLaunchExemption(wallet.publicKey)
+      .accounts({
+        tokenInfo: tokenInfoPda,
+        authority: wallet.publicKey,
+      })
+      .signers([wallet.payer])
+      .rpc();
+
+    await program.methods
+      .setLaunchSchedule(new anchor.BN(now + 60), new anchor.BN(600), new anchor.BN(1_000), new anchor.BN(10_000))
+      .accounts({
+        tokenInfo: tokenInfoPda,
+        authority: wallet.publicKey,
+      })
+      .signers([wallet.payer])
+      .rpc();
+
+    const tokenInfo = await program.account.tokenInfo.fetch(tokenInfoPda);
+    assert.equal(tokenInfo.tradingOpensAt.toNumber(), now + 60);
+    assert.isTrue(tokenInfo.launchExemptions.some((address) => address.equals(wallet.publicKey)));
+
+    await program.methods
+      .setLaunchSchedule(new anchor.BN(0), new anchor.BN(0), new anchor.BN(0), new anchor.BN(0))
+      .accounts({
+        tokenInfo: tokenInfoPda,
+        authority: wallet.publicKey,
+      })
+      .signers([wallet.payer])
+      .rpc();
+
+    await program.methods
+      .removeLaunchExemption(wallet.publicKey)
+      .accounts({
+        tokenInfo: tokenInfoPda,
+        authority: wallet.publicKey,
+      })
+      .signers([wallet.payer])
+      .rpc();
+  });
+
+  it("Creates and closes a vesting lockup", async () => {
+    const investor = Keypair.generate().publicKey;
+    const now = Math.floor(Date.now() / 1000);
+    const [lockupPda] = anchor.web3.PublicKey.findProgramAddressSync(
+      [Buffer.from('lockup'), mint2022.publicKey.toBuffer(), investor.toBuffer()],
+      program.programId,
+    );
+
+    await program.methods
+      .createLockup(
+        investor,
+        new anchor.BN(500_000), // total_locked
+        new anchor.BN(now), // start_time
+        new anchor.BN(now + 90 * 24 * 60 * 60), // cliff_time
+        new anchor.BN(now + 365 * 24 * 60 * 60) // end_time
+      )
+      .accounts({
+        tokenInfo: tokenInfoPda,
+        changeQueue: null,
+      })
+      .signers([wallet.payer])
+      .rpc();
+
+    const lockup = await program.account.lockup.fetch(lockupPda);
+    assert.equal(lockup.totalLocked.toNumber(), 500_000);
+
+    const closeTx = await program.methods
+      .closeLockup()
+      .accounts({
+        tokenInfo: tokenInfoPda,
+        lockup: lockupPda,
+      })
+      .signers([wallet.payer])
+      .rpc({ commitment: "confirmed" });
+
+    const closed = (await parseEvents(program, closeTx)).find((e) => /^lockupClosedEvent$/i.test(e.name));
+    assert.isDefined(closed);
+    assert.equal(closed.data.totalLocked.toNumber(), 500_000);
+
+    assert.isNull(await provider.connection.getAccountInfo(lockupPda));
+  });
+
+  it("Pauses and unpauses transfers", async () => {
+    await program.methods
+      .pause()
+      .accounts({
+        tokenInfo: tokenInfoPda,
+        pauseAuthority: wallet.publicKey,
+      })
+      .signers([wallet.payer])
+      .rpc();
+
+    let tokenInfo = await program.account.tokenInfo.fetch(tokenInfoPda);
+    assert.isTrue(tokenInfo.isPaused);
+
+    await program.methods
+      .unpause()
+      .accounts({
+        tokenInfo: tokenInfoPda,
+        pauseAuthority: wallet.publicKey,
+      })
+      .signers([wallet.payer])
+      .rpc();
+
+    tokenInfo = await program.account.tokenInfo.fetch(tokenInfoPda);
+    assert.isFalse(tokenInfo.isPaused);
+
+    // The pause authority is the pauser role
+    const incidentKey = Keypair.generate();
+    await program.methods
+      .setPauseAuthority(incidentKey.publicKey)
+      .accounts({
+        tokenInfo: tokenInfoPda,
+        authority: wallet.publicKey,
+      })
+      .signers([wallet.payer])
+      .rpc();
+
+    tokenInfo = await program.account.tokenInfo.fetch(tokenInfoPda);
+    assert.isTrue(tokenInfo.pauseAuthority.equals(incidentKey.publicKey));
+
+    await program.methods
+      .setRole({ pauser: {} }, wallet.publicKey)
+      .accounts({
+        tokenInfo: tokenInfoPda,
+        authority: wallet.publicKey,
+      })
+      .signers([wallet.payer])
+      .rpc();
+  });
+
+  it("Hands the admin role over in two steps", async () => {
+    const newAdmin = Keypair.generate();
+
+    await program.methods
+      .proposeAdmin(newAdmin.publicKey)
+      .accounts({
+        tokenInfo: tokenInfoPda,
+        authority: wallet.publicKey,
+      })
+      .signers([wallet.payer])
+      .rpc();
+
+    await program.methods
+      .acceptAdmin()
+      .accounts({
+        tokenInfo: tokenInfoPda,
+        newAdmin: newAdmin.publicKey,
+      })
+      .signers([newAdmin])
+      .rpc();
+
+    let tokenInfo = await program.account.tokenInfo.fetch(tokenInfoPda);
+    assert.isTrue(tokenInfo.tokenCreator.equals(newAdmin.publicKey));
+    assert.isTrue(tokenInfo.roleAdmin.equals(newAdmin.publicKey));
+    assert.isTrue(tokenInfo.pauseAuthority.equals(newAdmin.publicKey));
+
+    // The old key lost the role admin along with the admin role
+    try {
+      await program.methods
+        .setRole({ pauser: {} }, wallet.publicKey)
+        .accounts({
+          tokenInfo: tokenInfoPda,
+          authority: wallet.publicKey,
+        })
+        .signers([wallet.payer])
+        .rpc();
+      assert.fail("Expected the old admin to be rejected");
+    } catch (err) {
+      assert.include(err.toString(), "MissingRole");
+    }
+
+    await program.methods
+      .setRole({ pauser: {} }, wallet.publicKey)
+      .accounts({
+        tokenInfo: tokenInfoPda,
+        authority: newAdmin.publicKey,
+      })
+      .signers([newAdmin])
+      .rpc();
+
+    tokenInfo = await program.account.tokenInfo.fetch(tokenInfoPda);
+    assert.isTrue(tokenInfo.pauseAuthority.equals(wallet.publicKey));
+
+    // Hand it back for the remaining tests
+    await program.methods
+      .proposeAdmin(wallet.publicKey)
+      .accounts({
+        tokenInfo: tokenInfoPda,
+        authority: newAdmin.publicKey,
+      })
+      .signers([newAdmin])
+      .rpc();
+
+    await program.methods
+      .acceptAdmin()
+      .accounts({
+        tokenInfo: tokenInfoPda,
+        newAdmin: wallet.publicKey,
+      })
+      .signers([wallet.payer])
+      .rpc();
+
+    tokenInfo = await program.account.tokenInfo.fetch(tokenInfoPda);
+    assert.isTrue(tokenInfo.tokenCreator.equals(wallet.publicKey));
+    assert.isTrue(tokenInfo.roleAdmin.equals(wallet.publicKey));
+    assert.isTrue(tokenInfo.whitelistManager.equals(wallet.publicKey));
+    assert.isTrue(tokenInfo.limitsManager.equals(wallet.publicKey));
+    assert.isNull(tokenInfo.pendingTokenCreator);
+  });
+
+  it("Separates whitelist and limits roles", async () => {
+    const compliance = Keypair.generate();
+
+    await program.methods
+      .setRole({ whitelistManager: {} }, compliance.publicKey)
+      .accounts({
+        tokenInfo: tokenInfoPda,
+        authority: wallet.publicKey,
+      })
+      .signers([wallet.payer])
+      .rpc();
+
+    await program.methods
+      .updateDenylist(false)
+      .accounts({
+        tokenInfo: tokenInfoPda,
+        authority: compliance.publicKey,
+      })
+      .signers([compliance])
+      .rpc();
+
+    try {
+      await program.methods
+        .setMaxTransferLimit(new anchor.BN(1))
+        .accounts({
+          tokenInfo: tokenInfoPda,
+          authority: compliance.publicKey,
+        })
+        .signers([compliance])
+        .rpc();
+      assert.fail("Expected the whitelist manager to be refused a limits change");
+    } catch (err) {
+      assert.include(err.toString(), "MissingRole");
+    }
+
+    // Hand the role back for the remaining tests
+    await program.methods
+      .setRole({ whitelistManager: {} }, wallet.publicKey)
+      .accounts({
+        tokenInfo: tokenInfoPda,
+        authority: wallet.publicKey,
+      })
+      .signers([wallet.payer])
+      .rpc();
+
+    const tokenInfo = await program.account.tokenInfo.fetch(tokenInfoPda);
+    assert.isTrue(tokenInfo.whitelistManager.equals(wallet.publicKey));
+  });
+
+  it("Emits events for admin changes", async () => {
+    const tx = await program.methods
+      .setTransferQuota(true, new anchor.BN(42), new anchor.BN(60))
+      .accounts({
+        tokenInfo: tokenInfoPda,
+        authority: wallet.publicKey,
+      })
+      .signers([wallet.payer])
+      .rpc({ commitment: "confirmed" });
+
+    const events = await parseEvents(program, tx);
+    const event = events.find((e) => /^transferQuotaChangedEvent$/i.test(e.name));
+    assert.isDefined(event);
+    assert.isTrue(event.data.authority.equals(wallet.publicKey));
+
+    await program.methods
+      .setTransferQuota(false, new anchor.BN(0), new anchor.BN(0))
+      .accounts({
+        tokenInfo: tokenInfoPda,
+        authority: wallet.publicKey,
+      })
+      .signers([wallet.payer])
+      .rpc();
+  });
+
+  it("Updates whale alert settings", async () => {
+    const tx = await program.methods
+      .updateWhaleAlert(true, new anchor.BN(5000))
+      .accounts({
+        tokenInfo: tokenInfoPda,
+        authority: wallet.publicKey,
+      })
+      .signers([wallet.payer])
+      .rpc();
+
+    let whaleAlert = await findRule("whaleAlert");
+    assert.deepEqual(whaleAlert.thresholds.map((threshold) => threshold.toNumber()), [5000]);
+
+    await program.methods
+      .setWhaleTiers([new anchor.BN(5000), new anchor.BN(50_000), new anchor.BN(500_000)])
+      .accounts({
+        tokenInfo: tokenInfoPda,
+        authority: wallet.publicKey,
+      })
+      .signers([wallet.payer])
+      .rpc();
+
+    whaleAlert = await findRule("whaleAlert");
+    assert.equal(whaleAlert.thresholds.length, 3);
+
+    try {
+      await program.methods
+        .setWhaleTiers([new anchor.BN(50_000), new anchor.BN(5000)])
+        .accounts({
+          tokenInfo: tokenInfoPda,
+          authority: wallet.publicKey,
+        })
+        .signers([wallet.payer])
+        .rpc();
+      assert.fail("Expected descending thresholds to be rejected");
+    } catch (err) {
+      assert.include(err.toString(), "InvalidWhaleTiers");
+    }
+
+    // The 500,000 tier cannot be read as basis points
+    try {
+      await program.methods
+        .setWhaleAlertMode({ supplyBasisPoints: {} })
+        .accounts({
+          tokenInfo: tokenInfoPda,
+          authority: wallet.publicKey,
+        })
+        .signers([wallet.payer])
+        .rpc();
+      assert.fail("Expected more than 100% of supply to be rejected");
+    } catch (err) {
+      assert.include(err.toString(), "InvalidBasisPoints");
+    }
+
+    console.log("Whale alert updated:", tx);
+  });
+
+  it("Updates total transfer limit", async () => {
+    const tx = await program.methods
+      .setMaxTransferLimit(new anchor.BN(250_000))
+      .accounts({
+        tokenInfo: tokenInfoPda,
+        authority: wallet.publicKey,
+      })
+      .signers([wallet.payer])
+      .rpc();
+
+    console.log("Max transfer limit updated:", tx);
+  });
+
+  it("Sets a supply-relative transfer limit", async () => {
+    // 250,000 base units cannot be read as basis points
+    try {
+      await program.methods
+        .setTransferLimitMode({ supplyBasisPoints: {} })
+        .accounts({
+          tokenInfo: tokenInfoPda,
+          authority: wallet.publicKey,
+        })
+        .signers([wallet.payer])
+        .rpc();
+      assert.fail("Expected more than 100% of supply to be rejected");
+    } catch (err) {
+      assert.include(err.toString(), "InvalidBasisPoints");
+    }
+
+    await program.methods
+      .setMaxTransferLimit(new anchor.BN(100))
+      .accounts({
+        tokenInfo: tokenInfoPda,
+        authority: wallet.publicKey,
+      })
+      .signers([wallet.payer])
+      .rpc();
+
+    await program.methods
+      .setTransferLimitMode({ supplyBasisPoints: {} })
+      .accounts({
+        tokenInfo: tokenInfoPda,
+        authority: wallet.publicKey,
+      })
+      .signers([wallet.payer])
+      .rpc();
+
+    let limit = await findRule("maxTransferAmount");
+    assert.property(limit.mode, "supplyBasisPoints");
+    assert.equal(limit.limit.toNumber(), 100);
+
+    try {
+      await program.methods
+        .setMaxTransferLimit(new anchor.BN(10_001))
+        .accounts({
+          tokenInfo: tokenInfoPda,
+          authority: wallet.publicKey,
+        })
+        .signers([wallet.payer])
+        .rpc();
+      assert.fail("Expected more than 100% of supply to be rejected");
+    } catch (err) {
+      assert.include(err.toString(), "InvalidBasisPoints");
+    }
+
+    await program.methods
+      .setTransferLimitMode({ absolute: {} })
+      .accounts({
+        tokenInfo: tokenInfoPda,
+        authority: wallet.publicKey,
+      })
+      .signers([wallet.payer])
+      .rpc();
+
+    limit = await findRule("maxTransferAmount");
+    assert.property(limit.mode, "absolute");
+  });
+
+  it("Sets a decaying transfer limit schedule", async () => {
+    const now = Math.floor(Date.now() / 1000);
+
+    const tx = await program.methods
+      .setMaxTransferLimitSchedule({
+        startLimit: new anchor.BN(10_000),
+        endLimit: new anchor.BN(250_000),
+        startTime: new anchor.BN(now),
+        duration: new anchor.BN(7 * 24 * 60 * 60),
+      })
+      .accounts({
+        tokenInfo: tokenInfoPda,
+      })
+      .signers([wallet.payer])
+      .rpc();
+
+    const decaying = await findRule("decayingTransferLimit");
+    assert.equal(decaying.schedule.endLimit.toNumber(), 250_000);
+    // The schedule replaces the fixed limit
+    assert.isUndefined(await findRule("maxTransferAmount"));
+
+    await program.methods
+      .setMaxTransferLimit(new anchor.BN(250_000))
+      .accounts({
+        tokenInfo: tokenInfoPda,
+        authority: wallet.publicKey,
+      })
+      .signers([wallet.payer])
+      .rpc();
+
+    console.log("Transfer limit schedule set:", tx);
+  });
+
+  it("Runs the hook on Token-2022 transfers", async () => {
+    const decimals = 9;
+    const outsider = Keypair.generate();
+    const outsiderTokenAccount = getAssociatedTokenAddressSync(
+      mint2022.publicKey,
+      outsider.publicKey,
+      false,
+      TOKEN_2022_PROGRAM_ID,
+      ASSOCIATED_TOKEN_PROGRAM_ID,
+    );
+
+    // Fund the whitelisted sender and a sender that is not on the whitelist
+    await sendAndConfirmTransaction(
+      provider.connection,
+      new Transaction().add(
+        ...[wallet.publicKey, recipient.publicKey, outsider.publicKey].map((owner) =>
+          createAssociatedTokenAccountIdempotentInstruction(
+            wallet.publicKey,
+            getAssociatedTokenAddressSync(
+              mint2022.publicKey,
+              owner,
+              false,
+              TOKEN_2022_PROGRAM_ID,
+              ASSOCIATED_TOKEN_PROGRAM_ID,
+            ),
+            owner,
+            mint2022.publicKey,
+            TOKEN_2022_PROGRAM_ID,
+            ASSOCIATED_TOKEN_PROGRAM_ID,
+          ),
+        ),
+        createMintToInstruction(mint2022.publicKey, sourceTokenAccount, wallet.publicKey, 1_000_000, [], TOKEN_2022_PROGRAM_ID),
+        createMintToInstruction(mint2022.publicKey, outsiderTokenAccount, wallet.publicKey, 10_000, [], TOKEN_2022_PROGRAM_ID),
+      ),
+      [wallet.payer],
+      { commitment: "confirmed" },
+    );
+
+    // The Whitelist rule checks the source owner
+    await program.methods
+      .addToWhitelist(wallet.publicKey)
+      .accounts({
+        tokenInfo: tokenInfoPda,
+      })
+      .signers([wallet.payer])
+      .rpc();
+
+    // 60,000 is under the 250,000 limit and crosses the 5,000 and 50,000 whale tiers
+    const transferIx = await createTransferCheckedWithTransferHookInstruction(
+      provider.connection,
+      sourceTokenAccount,
+      mint2022.publicKey,
+      destinationTokenAccount,
+      wallet.publicKey,
+      BigInt(60_000),
+      decimals,
+      [],
+      "confirmed",
+      TOKEN_2022_PROGRAM_ID,
+    );
+    const signature = await sendAndConfirmTransaction(
+      provider.connection,
+      new Transaction().add(transferIx),
+      [wallet.payer],
+      { commitment: "confirmed" },
+    );
+
+    const destination = await provider.connection.getTokenAccountBalance(destinationTokenAccount);
+    assert.equal(destination.value.amount, "60000");
+
+    const whale = (await parseEvents(program, signature)).find((e) => /^whaleTransferEvent$/i.test(e.name));
+    assert.isDefined(whale);
+    assert.equal(whale.data.tier, 2);
+    assert.equal(whale.data.transferAmount.toNumber(), 60_000);
+    assert.isTrue(whale.data.whaleAddress.equals(wallet.publicKey));
+    assert.isTrue(whale.data.destinationOwner.equals(recipient.publicKey));
+
+    const rejectedIx = await createTransferCheckedWithTransferHookInstruction(
+      provider.connection,
+      outsiderTokenAccount,
+      mint2022.publicKey,
+      destinationTokenAccount,
+      outsider.publicKey,
+      BigInt(1_000),
+      decimals,
+      [],
+      "confirmed",
+      TOKEN_2022_PROGRAM_ID,
+    );
+    try {
+      await sendAndConfirmTransaction(
+        provider.connection,
+        new Transaction().add(rejectedIx),
+        [wallet.payer, outsider],
+        { commitment: "confirmed" },
+      );
+      assert.fail("Expected the transfer from a non-whitelisted owner to be rejected");
+    } catch (err) {
+      assert.include((err.logs ?? []).join("\n"), "NotWhitelisted");
+    }
+
+    const outsiderBalance = await provider.connection.getTokenAccountBalance(outsiderTokenAccount);
+    assert.equal(outsiderBalance.value.amount, "10000");
+  });
+
+  it("Collects transfer statistics", async () => {
+    const [transferStatsPda] = anchor.web3.PublicKey.findProgramAddressSync(
+      [Buffer.from('stats'), mint2022.publicKey.toBuffer()],
+      program.programId,
+    );
+
+    await program.methods
+      .initializeTransferStats()
+      .accounts({
+        tokenInfo: tokenInfoPda,
+        authority: wallet.publicKey,
+      })
+      .signers([wallet.payer])
+      .rpc();
+
+    const stats = await program.account.transferStats.fetch(transferStatsPda);
+    assert.ok(stats.tokenMint.equals(mint2022.publicKey));
+    assert.equal(stats.transferCount.toNumber(), 0);
+    assert.equal(stats.totalVolume.toNumber(), 0);
+    assert.equal(stats.largestTransfer.toNumber(), 0);
+    assert.equal(stats.whaleAlertCount.toNumber(), 0);
+
+    const tokenInfo = await program.account.tokenInfo.fetch(tokenInfoPda);
+    assert.isTrue(tokenInfo.hasTransferStats);
+
+    // The hook now writes to the stats account, which is in the meta list
+    const [metaListPda] = anchor.web3.PublicKey.findProgramAddressSync(
+      [Buffer.from('extra-account-metas'), mint2022.publicKey.toBuffer()],
+      program.programId,
+    );
+    const metaList = await provider.connection.getAccountInfo(metaListPda);
+    assert.isTrue(metaList.data.includes(Buffer.from('stats')));
+  });
+
+  it("Manages an ordered rule set", async () => {
+    // Created with the token info, holding the rules of its feature flags
+    let ruleSet = await program.account.ruleSet.fetch(ruleSetPda);
+    assert.property(ruleSet.rules[0], "whitelist");
+    const initialLength = ruleSet.rules.length;
+
+    for (const rule of [
+      { blockedWallets: { wallets: [Keypair.generate().publicKey] } },
+      { allowedRecipients: { wallets: [recipient.publicKey, wallet.publicKey] } },
+    ]) {
+      const tx = await program.methods
+        .addRule(rule)
+        .accounts({
+          tokenInfo: tokenInfoPda,
+          changeQueue: null,
+          authority: wallet.publicKey,
+        })
+        .signers([wallet.payer])
+        .rpc({ commitment: "confirmed" });
+
+      const events = await parseEvents(program, tx);
+      assert.isDefined(events.find((e) => /^ruleAddedEvent$/i.test(e.name)));
+    }
+
+    const moveTx = await program.methods
+      .moveRule(initialLength + 1, 0)
+      .accounts({
+        tokenInfo: tokenInfoPda,
+        changeQueue: null,
+        authority: wallet.publicKey,
+      })
+      .signers([wallet.payer])
+      .rpc({ commitment: "confirmed" });
+
+    const moved = (await parseEvents(program, moveTx)).find((e) => /^ruleMovedEvent$/i.test(e.name));
+    assert.isDefined(moved);
+    assert.equal(moved.data.oldIndex, initialLength + 1);
+    assert.equal(moved.data.newIndex, 0);
+
+    ruleSet = await program.account.ruleSet.fetch(ruleSetPda);
+    assert.property(ruleSet.rules[0], "allowedRecipients");
+    assert.property(ruleSet.rules[initialLength + 1], "blockedWallets");
+
+    for (const index of [initialLength + 1, 0]) {
+      const tx = await program.methods
+        .removeRule(index)
+        .accounts({
+          tokenInfo: tokenInfoPda,
+          changeQueue: null,
+          authority: wallet.publicKey,
+        })
+        .signers([wallet.payer])
+        .rpc({ commitment: "confirmed" });
+
+      const events = await parseEvents(program, tx);
+      assert.isDefined(events.find((e) => /^ruleRemovedEvent$/i.test(e.name)));
+    }
+
+    ruleSet = await program.account.ruleSet.fetch(ruleSetPda);
+    assert.equal(ruleSet.rules.length, initialLength);
+
+    await program.methods
+      .updateExtraAccountMetaList()
+      .accounts({
+        mint: mint2022.publicKey,
+        authority: wallet.publicKey,
+      })
+      .signers([wallet.payer])
+      .rpc();
+  });
+
+  // Leaves a one-hour timelock on, so keep this test last
+  it("Routes sensitive changes through the timelock queue", async () => {
+    const [changeQueuePda] = anchor.web3.PublicKey.findProgramAddressSync(
+      [Buffer.from('change-queue'), mint2022.publicKey.toBuffer()],
+      program.programId,
+    );
+
+    // Without a delay a queued change is executable right away
+    await program.methods
+      .queueChange({ maxTransferLimit: { limit: new anchor.BN(200_000), mode: { absolute: {} } } })
+      .accounts({
+        tokenInfo: tokenInfoPda,
+        authority: wallet.publicKey,
+      })
+      .signers([wallet.payer])
+      .rpc();
+
+    let queue = await program.account.changeQueue.fetch(changeQueuePda);
+    await program.methods
+      .executeChange(queue.changes[0].id)
+      .accounts({
+        tokenInfo: tokenInfoPda,
+        authority: wallet.publicKey,
+      })
+      .signers([wallet.payer])
+      .rpc();
+
+    let limit = await findRule("maxTransferAmount");
+    assert.equal(limit.limit.toNumber(), 200_000);
+
+    await program.methods
+      .setTimelockDelay(new anchor.BN(60 * 60))
+      .accounts({
+        tokenInfo: tokenInfoPda,
+        authority: wallet.publicKey,
+      })
+      .signers([wallet.payer])
+      .rpc();
+
+    try {
+      await program.methods
+        .setMaxTransferLimit(new anchor.BN(1))
+        .accounts({
+          tokenInfo: tokenInfoPda,
+          authority: wallet.publicKey,
+        })
+        .signers([wallet.payer])
+        .rpc();
+      assert.fail("Expected the direct change to be refused");
+    } catch (err) {
+      assert.include(err.toString(), "TimelockRequired");
+    }
+
+    try {
+      await program.methods
+        .setTransferQuota(true, new anchor.BN(1), new anchor.BN(60))
+        .accounts({
+          tokenInfo: tokenInfoPda,
+          authority: wallet.publicKey,
+          })
+        .signers([wallet.payer])
+        .rpc();
+      assert.fail("Expected enabling the quota to be refused");
+    } catch (err) {
+      assert.include(err.toString(), "TimelockRequired");
+    }
+
+    // So do new rules that can block transfers
+    try {
+      await program.methods
+        .addRule({ blockedWallets: { wallets: [Keypair.generate().publicKey] } })
+        .accounts({
+          tokenInfo: tokenInfoPda,
+          changeQueue: changeQueuePda,
+          authority: wallet.publicKey,
+        })
+        .signers([wallet.payer])
+        .rpc();
+      assert.fail("Expected the new rule to be refused");
+    } catch (err) {
+      assert.include(err.toString(), "TimelockRequired");
+    }
+
+    // New denylist entries need a matured queued change
+    try {
+      await program.methods
+        .addToDenylist(Keypair.generate().publicKey)
+        .accounts({
+          tokenInfo: tokenInfoPda,
+          changeQueue: changeQueuePda,
+        })
+        .signers([wallet.payer])
+        .rpc();
+      assert.fail("Expected the new denylist entry to be refused");
+    } catch (err) {
+      assert.include(err.toString(), "TimelockRequired");
+    }
+
+    // The emergency pause stays immediate under the timelock
+    await program.methods
+      .pause()
+      .accounts({
+        tokenInfo: tokenInfoPda,
+        pauseAuthority: wallet.publicKey,
+      })
+      .signers([wallet.payer])
+      .rpc();
+
+    await program.methods
+      .unpause()
+      .accounts({
+        tokenInfo: tokenInfoPda,
+        pauseAuthority: wallet.publicKey,
+      })
+      .signers([wallet.payer])
+      .rpc();
+
+    await program.methods
+      .queueChange({ maxTransferLimit: { limit: new anchor.BN(1), mode: { absolute: {} } } })
+      .accounts({
+        tokenInfo: tokenInfoPda,
+        authority: wallet.publicKey,
+      })
+      .signers([wallet.payer])
+      .rpc();
+
+    queue = await program.account.changeQueue.fetch(changeQueuePda);
+    const changeId = queue.changes[0].id;
+
+    try {
+      await program.methods
+        .executeChange(changeId)
+        .accounts({
+          tokenInfo: tokenInfoPda,
+          authority: wallet.publicKey,
+          })
+        .signers([wallet.payer])
+        .rpc();
+      assert.fail("Expected the change to wait out the delay");
+    } catch (err) {
+      assert.include(err.toString(), "TimelockNotElapsed");
+    }
+
+    await program.methods
+      .cancelChange(changeId)
+      .accounts({
+        tokenInfo: tokenInfoPda,
+        authority: wallet.publicKey,
+      })
+      .signers([wallet.payer])
+      .rpc();
+
+    queue = await program.account.changeQueue.fetch(changeQueuePda);
+    assert.equal(queue.changes.length, 0);
+
+    limit = await findRule("maxTransferAmount");
+    assert.equal(limit.limit.toNumber(), 200_000);
   });
 });